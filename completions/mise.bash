@@ -1677,7 +1677,7 @@ _mise() {
             return 0
             ;;
         mise__doctor)
-            opts="-C -q -v -y -h --cd --debug --log-level --quiet --trace --verbose --yes --help"
+            opts="-C -q -v -y -h --versions --cd --debug --log-level --quiet --trace --verbose --yes --help"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -3709,7 +3709,7 @@ _mise() {
             return 0
             ;;
         mise__reshim)
-            opts="-C -q -v -y -h --cd --debug --log-level --quiet --trace --verbose --yes --help [PLUGIN] [VERSION]"
+            opts="-C -q -v -y -h --watch --cd --debug --log-level --quiet --trace --verbose --yes --help [PLUGIN] [VERSION]"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -3821,7 +3821,7 @@ _mise() {
             return 0
             ;;
         mise__set)
-            opts="-g -C -q -v -y -h --file --global --remove --cd --debug --log-level --quiet --trace --verbose --yes --help [ENV_VARS]..."
+            opts="-g -J -C -q -v -y -h --file --global --json --remove --cd --debug --log-level --quiet --trace --verbose --yes --help [ENV_VARS]..."
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
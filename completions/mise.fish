@@ -116,6 +116,7 @@ complete -xc mise -n "$fssf direnv; and not $fssf $others" -a activate -d 'Outpu
 
 
 # doctor
+complete -kxc mise -n "$fssf doctor" -l versions -d 'Check that the tool versions requested by config files exist'
 
 # env
 complete -kxc mise -n "$fssf env" -s J -l json -d 'Output in JSON format'
@@ -215,6 +216,7 @@ complete -kxc mise -n "$fssf prune" -s n -l dry-run -d 'Do not actually delete a
 complete -kxc mise -n "$fssf prune" -a "(__mise_plugins)" -d 'Prune only versions from this plugin(s)'
 
 # reshim
+complete -kxc mise -n "$fssf reshim" -l watch -d '[experimental] Keep running and rebuild the shims whenever a config file changes'
 
 # run
 complete -kxc mise -n "$fssf run" -d 'Arguments to pass to the task. Use ":::" to separate tasks'
@@ -239,6 +241,7 @@ complete -kxc mise -n "$fssf self-update" -s y -l yes -d 'Skip confirmation prom
 complete -kxc mise -n "$fssf set" -d 'Environment variable(s) to set'
 complete -kxc mise -n "$fssf set" -l file -a "(__fish_complete_path)" -d 'The TOML file to update'
 complete -kxc mise -n "$fssf set" -s g -l global -d 'Set the environment variable in the global config file'
+complete -kxc mise -n "$fssf set" -s J -l json -d 'Output the resolved environment variables and their sources as JSON'
 
 # settings
 set -l others get ls set unset
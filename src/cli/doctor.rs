@@ -24,6 +24,11 @@ use crate::{file, shims};
 #[derive(Debug, clap::Args)]
 #[clap(verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
 pub struct Doctor {
+    /// Check that the tool versions requested by config files exist
+    /// this fetches the remote versions of each tool so it can be slow
+    #[clap(long, verbatim_doc_comment)]
+    versions: bool,
+
     #[clap(skip)]
     checks: Vec<String>,
 }
@@ -82,6 +87,25 @@ impl Doctor {
         miseprintln!("{}", render_config_files(config));
         miseprintln!("{}", render_plugins());
 
+        for diagnostic in config.diagnose() {
+            self.checks.push(diagnostic.to_string());
+        }
+
+        if self.versions {
+            match config.validate_tools() {
+                Ok(diagnostics) => {
+                    for diagnostic in diagnostics {
+                        self.checks.push(diagnostic.to_string());
+                    }
+                }
+                Err(err) => self.checks.push(format!("failed to validate tools: {err}")),
+            }
+        }
+
+        if let Err(err) = config.validate_tasks() {
+            self.checks.push(err.to_string());
+        }
+
         for plugin in forge::list() {
             if !plugin.is_installed() {
                 self.checks
@@ -246,5 +270,8 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
     r#"<bold><underline>Examples:</underline></bold>
   $ <bold>mise doctor</bold>
   [WARN] plugin node is not installed
+
+  # also check that the requested tool versions exist
+  $ <bold>mise doctor --versions</bold>
 "#
 );
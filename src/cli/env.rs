@@ -48,7 +48,13 @@ impl Env {
     fn output_shell(&self, config: &Config, ts: Toolset) -> Result<()> {
         let default_shell = get_shell(Some(ShellType::Bash)).unwrap();
         let shell = get_shell(self.shell).unwrap_or(default_shell);
-        for (k, v) in ts.env_with_path(config)? {
+        let env = ts.env_with_path(config)?;
+        for k in config.env_remove()?.keys() {
+            if !env.contains_key(k) {
+                miseprint!("{}", shell.unset_env(k));
+            }
+        }
+        for (k, v) in env {
             let k = k.to_string();
             let v = v.to_string();
             miseprint!("{}", shell.set_env(&k, &v));
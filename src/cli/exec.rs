@@ -63,6 +63,11 @@ impl Exec {
 
         let (program, args) = parse_command(&env::SHELL, &self.command, &self.c);
         let env = ts.env_with_path(&config)?;
+        for k in config.env_remove()?.keys() {
+            if !env.contains_key(k) {
+                env::remove_var(k);
+            }
+        }
 
         self.exec(program, args, env)
     }
@@ -44,7 +44,13 @@ impl HookEnv {
         miseprint!("{}", hook_env::clear_old_env(&*shell));
         let mut env = ts.env(&config)?;
         let env_path = env.remove("PATH");
+        let env_remove = config.env_remove()?;
         let mut diff = EnvDiff::new(&env::PRISTINE_ENV, env);
+        for k in env_remove.keys() {
+            if let Some(orig) = env::PRISTINE_ENV.get(k) {
+                diff.old.insert(k.clone(), orig.clone());
+            }
+        }
         let mut patches = diff.to_patches();
 
         let mut paths = config.path_dirs()?.clone();
@@ -81,10 +87,16 @@ impl HookEnv {
             }
         }
         if self.status || settings.status.show_env {
-            let env_diff = EnvDiff::new(&env::PRISTINE_ENV, config.env()?.clone()).to_patches();
-            if !env_diff.is_empty() {
-                let env_diff = env_diff.into_iter().map(patch_to_status).join(" ");
-                info!("{}", truncate_str(&env_diff, TERM_WIDTH.max(60) - 5, "…"));
+            let diff = config.env_diff()?;
+            let status = diff
+                .added
+                .keys()
+                .map(|k| format!("+{k}"))
+                .chain(diff.changed.keys().map(|k| format!("~{k}")))
+                .chain(diff.removed.keys().map(|k| format!("-{k}")))
+                .join(" ");
+            if !status.is_empty() {
+                info!("{}", truncate_str(&status, TERM_WIDTH.max(60) - 5, "…"));
             }
         }
         ts.notify_if_versions_missing();
@@ -173,14 +185,6 @@ impl HookEnv {
     }
 }
 
-fn patch_to_status(patch: EnvDiffOperation) -> String {
-    match patch {
-        EnvDiffOperation::Add(k, _) => format!("+{}", k),
-        EnvDiffOperation::Change(k, _) => format!("~{}", k),
-        EnvDiffOperation::Remove(k) => format!("-{}", k),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     #[test]
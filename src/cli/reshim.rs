@@ -1,8 +1,16 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
 use eyre::Result;
+use signal_hook::consts::SIGINT;
 
-use crate::config::Config;
-use crate::shims;
+use crate::config::{Config, Settings};
 use crate::toolset::ToolsetBuilder;
+use crate::{dirs, file, shims};
 
 /// rebuilds the shim farm
 ///
@@ -26,15 +34,110 @@ pub struct Reshim {
     pub plugin: Option<String>,
     #[clap(hide = true)]
     pub version: Option<String>,
+
+    /// [experimental] Keep running and rebuild the shims whenever a config file changes
+    #[clap(long, verbatim_doc_comment)]
+    pub watch: bool,
 }
 
 impl Reshim {
     pub fn run(self) -> Result<()> {
+        if self.watch {
+            return self.watch();
+        }
         let config = Config::try_get()?;
         let ts = ToolsetBuilder::new().build(&config)?;
 
         shims::reshim(&ts)
     }
+
+    /// polls the config's watch files, rebuilding shims and runtime symlinks once they
+    /// stop changing. Runs until ctrl-c.
+    fn watch(&self) -> Result<()> {
+        Settings::get().ensure_experimental()?;
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let sig = signal_hook::flag::register(SIGINT, interrupted.clone())?;
+
+        let mut config = Config::load()?;
+        config.rebuild_shims_and_runtime_symlinks()?;
+        let mut watched = mtimes(watch_files(&config, &BTreeMap::new())?);
+        info!("watching {} files for changes", watched.len());
+        while !interrupted.load(Ordering::Relaxed) {
+            sleep(POLL_INTERVAL);
+            if !changed(&config, &mut watched) {
+                continue;
+            }
+            // editors often write a file more than once when saving
+            loop {
+                sleep(DEBOUNCE);
+                if !changed(&config, &mut watched) {
+                    break;
+                }
+            }
+            info!("config changed, rebuilding shims");
+            Settings::reload();
+            match Config::load().and_then(|c| c.rebuild_shims_and_runtime_symlinks().map(|_| c)) {
+                Ok(c) => config = c,
+                Err(err) => warn!("failed to rebuild shims: {err:#}"),
+            }
+            // includes and env files may have changed what needs to be watched
+            match watch_files(&config, &watched) {
+                Ok(files) => watched = mtimes(files),
+                Err(err) => {
+                    warn!("failed to update watch files: {err:#}");
+                    watched = mtimes(watched.into_keys());
+                }
+            }
+        }
+        signal_hook::low_level::unregister(sig);
+        debug!("Ctrl-C pressed, stopped watching");
+        Ok(())
+    }
+}
+
+static POLL_INTERVAL: Duration = Duration::from_millis(500);
+static DEBOUNCE: Duration = Duration::from_millis(200);
+
+type Mtimes = BTreeMap<PathBuf, Option<SystemTime>>;
+
+/// the config's watch files, config files created since it was loaded, the install dirs and
+/// any previously watched files that were deleted so they are picked up again if recreated
+fn watch_files(config: &Config, prev: &Mtimes) -> Result<Vec<PathBuf>> {
+    let mut files = config.watch_files()?;
+    files.extend(config.find_config_paths());
+    // a new tool or version creates a directory here
+    files.insert(dirs::INSTALLS.to_path_buf());
+    for tool in file::dir_subdirs(&dirs::INSTALLS)? {
+        files.insert(dirs::INSTALLS.join(tool));
+    }
+    files.extend(prev.keys().filter(|p| !p.exists()).cloned());
+    Ok(files.into_iter().collect())
+}
+
+/// refreshes `watched`, true if a file was added, removed or modified since the last call
+/// the watch files are listed again each time since new config files can appear anywhere
+fn changed(config: &Config, watched: &mut Mtimes) -> bool {
+    let current = match watch_files(config, watched) {
+        Ok(files) => mtimes(files),
+        Err(err) => {
+            // this runs on every poll so it isn't a warning, rebuilding shims reports it
+            debug!("failed to list watch files: {err:#}");
+            mtimes(watched.keys().cloned())
+        }
+    };
+    let changed = current != *watched;
+    *watched = current;
+    changed
+}
+
+fn mtimes(files: impl IntoIterator<Item = PathBuf>) -> Mtimes {
+    files
+        .into_iter()
+        .map(|f| {
+            let mtime = f.metadata().and_then(|m| m.modified()).ok();
+            (f, mtime)
+        })
+        .collect()
 }
 
 static AFTER_LONG_HELP: &str = color_print::cstr!(
@@ -42,5 +145,25 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
   $ <bold>mise reshim</bold>
   $ <bold>~/.local/share/mise/shims/node -v</bold>
   v20.0.0
+
+  # rebuild shims whenever a config file changes
+  $ <bold>mise reshim --watch</bold>
 "#
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_new_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::load_from_dir(dir.path()).unwrap();
+        let mut watched = mtimes(watch_files(&config, &BTreeMap::new()).unwrap());
+        let path = dir.path().join(".test.mise.toml");
+        assert!(!watched.contains_key(&path));
+        file::write(&path, "[tools]\ntiny = \"3\"\n").unwrap();
+        assert!(changed(&config, &mut watched));
+        assert!(watched.contains_key(&path));
+    }
+}
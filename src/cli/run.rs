@@ -304,6 +304,11 @@ impl Run {
     ) -> Result<()> {
         let program = program.to_executable();
         let mut cmd = CmdLineRunner::new(program.clone()).args(args).envs(env);
+        for k in Config::get().env_remove()?.keys() {
+            if !env.contains_key(k) {
+                cmd = cmd.env_remove(k);
+            }
+        }
         cmd.with_pass_signals();
         match &self.output(task)? {
             TaskOutput::Prefix => cmd = cmd.prefix(format!("{prefix} ")),
@@ -390,6 +395,7 @@ impl Run {
     }
 
     fn validate_task(&self, task: &Task) -> Result<()> {
+        task.verify_checksum()?;
         if let Some(path) = &task.file {
             if !file::is_executable(path) {
                 let dp = display_path(path);
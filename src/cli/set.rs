@@ -28,6 +28,10 @@ pub struct Set {
     #[clap(short, long, verbatim_doc_comment, overrides_with = "file")]
     global: bool,
 
+    /// Output the resolved environment variables and their sources as JSON
+    #[clap(short = 'J', long, verbatim_doc_comment, conflicts_with_all = ["env_vars", "remove"])]
+    json: bool,
+
     /// Remove the environment variable from config file
     ///
     /// Can be used multiple times.
@@ -43,6 +47,10 @@ pub struct Set {
 impl Set {
     pub fn run(self) -> Result<()> {
         let config = Config::try_get()?;
+        if self.json {
+            miseprintln!("{}", serde_json::to_string_pretty(&config.env_json()?)?);
+            return Ok(());
+        }
         if self.remove.is_none() && self.env_vars.is_none() {
             let rows = config
                 .env_with_sources()?
@@ -120,6 +128,15 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
   $ <bold>mise set</bold>
   key       value       source
   NODE_ENV  production  ~/.config/mise/config.toml
+
+  $ <bold>mise set --json</bold>
+  {
+    "NODE_ENV": {
+      "value": "production",
+      "source": "/home/user/.config/mise/config.toml",
+      "type": "config"
+    }
+  }
 "#
 );
 
@@ -127,6 +144,7 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
 mod tests {
     use std::path::PathBuf;
 
+    use crate::test::replace_path;
     use crate::{env, file};
 
     fn remove_config_file(filename: &str) -> PathBuf {
@@ -140,6 +158,12 @@ mod tests {
         assert_cli_snapshot!("env-vars");
     }
 
+    #[test]
+    fn test_show_env_vars_json() {
+        let output = assert_cli!("env-vars", "--json");
+        assert_snapshot!(replace_path(&output));
+    }
+
     #[test]
     fn test_env_vars() {
         // Using the default file
@@ -1,6 +1,7 @@
 use eyre::Result;
 use toml_edit::Document;
 
+use crate::config::merge::MergeStrategy;
 use crate::{env, file};
 
 /// Add/update a setting
@@ -29,8 +30,11 @@ impl SettingsSet {
             "experimental" => parse_bool(&self.value)?,
             "jobs" => parse_i64(&self.value)?,
             "legacy_version_file" => parse_bool(&self.value)?,
+            "legacy_version_manifests" => parse_bool(&self.value)?,
+            "merge_strategy" => parse_merge_strategy(&self.value)?,
             "node_compile" => parse_bool(&self.value)?,
             "not_found_auto_install" => parse_bool(&self.value)?,
+            "override_config_filename" => self.value.into(),
             "paranoid" => parse_bool(&self.value)?,
             "plugin_autoupdate_last_check_duration" => parse_i64(&self.value)?,
             "python_compile" => parse_bool(&self.value)?,
@@ -38,6 +42,9 @@ impl SettingsSet {
             "quiet" => parse_bool(&self.value)?,
             "raw" => parse_bool(&self.value)?,
             "shorthands_file" => self.value.into(),
+            "skip_invalid_config_files" => parse_bool(&self.value)?,
+            "strict_config" => parse_bool(&self.value)?,
+            "strict_env" => parse_bool(&self.value)?,
             "task_output" => self.value.into(),
             "trusted_config_paths" => self.value.split(':').map(|s| s.to_string()).collect(),
             "verbose" => parse_bool(&self.value)?,
@@ -66,6 +73,13 @@ fn parse_bool(value: &str) -> Result<toml_edit::Value> {
     }
 }
 
+fn parse_merge_strategy(value: &str) -> Result<toml_edit::Value> {
+    match value.parse::<MergeStrategy>() {
+        Ok(strategy) => Ok(strategy.to_string().into()),
+        Err(_) => Err(eyre!("{} must be append or replace", value)),
+    }
+}
+
 fn parse_i64(value: &str) -> Result<toml_edit::Value> {
     match value.parse::<i64>() {
         Ok(value) => Ok(value.into()),
@@ -109,8 +123,12 @@ pub mod tests {
         jobs = 4
         legacy_version_file = true
         legacy_version_file_disable_tools = []
+        legacy_version_filenames = {}
+        legacy_version_manifests = true
+        merge_strategy = "append"
         node_compile = false
         not_found_auto_install = true
+        override_config_filename = ".mise.override.toml"
         paranoid = false
         plugin_autoupdate_last_check_duration = "7d"
         python_compile = false
@@ -124,7 +142,10 @@ pub mod tests {
         quiet = false
         raw = false
         shorthands_file = null
+        skip_invalid_config_files = false
         status = {"missing_tools":true,"show_env":false,"show_tools":false}
+        strict_config = false
+        strict_env = false
         task_output = null
         trusted_config_paths = []
         verbose = true
@@ -53,8 +53,12 @@ mod tests {
         jobs = 2
         legacy_version_file = true
         legacy_version_file_disable_tools = []
+        legacy_version_filenames = {}
+        legacy_version_manifests = true
+        merge_strategy = "append"
         node_compile = false
         not_found_auto_install = true
+        override_config_filename = ".mise.override.toml"
         paranoid = false
         plugin_autoupdate_last_check_duration = "20m"
         python_compile = false
@@ -68,7 +72,10 @@ mod tests {
         quiet = false
         raw = false
         shorthands_file = null
+        skip_invalid_config_files = false
         status = {"missing_tools":true,"show_env":false,"show_tools":false}
+        strict_config = false
+        strict_env = false
         task_output = null
         trusted_config_paths = []
         verbose = true
@@ -32,13 +32,19 @@ impl TaskLs {
         let config = Config::try_get()?;
         let settings = Settings::try_get()?;
         settings.ensure_experimental()?;
+        config.validate_tasks()?;
         let rows = config
             .tasks()
             .iter()
-            .map(|(_, t)| t)
+            .map(|(_, t)| {
+                t.clone().fetch_remote().unwrap_or_else(|err| {
+                    warn!("Error loading task {}: {err:#}", t.name);
+                    t.clone()
+                })
+            })
             .sorted()
             .filter(|t| self.hidden || !t.hide)
-            .map(|t| t.into())
+            .map(|t| (&t).into())
             .collect::<Vec<Row>>();
         let mut table = tabled::Table::new(rows);
         table::default_style(&mut table, self.no_header);
@@ -4,7 +4,7 @@ use clap::ValueHint;
 use eyre::Result;
 
 use crate::config;
-use crate::config::{config_file, DEFAULT_CONFIG_FILENAMES};
+use crate::config::{config_file, Settings, DEFAULT_CONFIG_FILENAMES};
 
 /// Marks a config file as trusted
 ///
@@ -72,14 +72,20 @@ impl Trust {
     }
 
     fn get_next_trusted(&self) -> Option<PathBuf> {
-        config::load_config_paths(&DEFAULT_CONFIG_FILENAMES)
-            .into_iter()
-            .find(|p| config_file::is_trusted(p))
+        config::load_config_paths(
+            &DEFAULT_CONFIG_FILENAMES,
+            &Settings::get().override_config_filename,
+        )
+        .into_iter()
+        .find(|p| config_file::is_trusted(p))
     }
     fn get_next_untrusted(&self) -> Option<PathBuf> {
-        config::load_config_paths(&DEFAULT_CONFIG_FILENAMES)
-            .into_iter()
-            .find(|p| !config_file::is_trusted(p))
+        config::load_config_paths(
+            &DEFAULT_CONFIG_FILENAMES,
+            &Settings::get().override_config_filename,
+        )
+        .into_iter()
+        .find(|p| !config_file::is_trusted(p))
     }
 }
 
@@ -157,6 +157,13 @@ impl<'a> CmdLineRunner<'a> {
         self.cmd.env(key, val);
         self
     }
+    pub fn env_remove<K>(mut self, key: K) -> Self
+    where
+        K: AsRef<OsStr>,
+    {
+        self.cmd.env_remove(key);
+        self
+    }
     pub fn envs<I, K, V>(mut self, vars: I) -> Self
     where
         I: IntoIterator<Item = (K, V)>,
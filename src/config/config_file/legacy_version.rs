@@ -1,10 +1,13 @@
 use std::default::Default;
 use std::path::{Path, PathBuf};
 
-use eyre::Result;
+use eyre::{Context, Result};
 
 use crate::cli::args::ForgeArg;
+use crate::config::config_file;
 use crate::config::config_file::{ConfigFile, ConfigFileType};
+use crate::file;
+use crate::file::display_path;
 use crate::forge::ForgeList;
 use crate::toolset::{ToolSource, ToolVersionRequest, Toolset};
 
@@ -12,14 +15,34 @@ use crate::toolset::{ToolSource, ToolVersionRequest, Toolset};
 pub struct LegacyVersionFile {
     path: PathBuf,
     toolset: Toolset,
+    project_root: Option<PathBuf>,
 }
 
 impl LegacyVersionFile {
     pub fn parse(path: PathBuf, plugins: ForgeList) -> Result<Self> {
         let mut toolset = Toolset::new(ToolSource::LegacyVersionFile(path.clone()));
 
+        let filename = path.file_name().unwrap_or_default().to_string_lossy();
         for plugin in plugins {
-            let version = plugin.parse_legacy_file(&path)?;
+            let manifest = plugin
+                .legacy_manifests()
+                .into_iter()
+                .find(|m| m.filename() == filename);
+            let version = match manifest {
+                // manifests are shared with other tooling so a broken one shouldn't stop mise
+                Some(manifest) => match manifest.extract(&path) {
+                    Ok(version) => version.unwrap_or_default(),
+                    Err(err) => {
+                        warn!(
+                            "failed to read {} version from {}: {err:#}",
+                            plugin.id(),
+                            display_path(&path)
+                        );
+                        continue;
+                    }
+                },
+                None => plugin.parse_legacy_file(&path)?,
+            };
             for version in version.split_whitespace() {
                 toolset.add_version(
                     ToolVersionRequest::new(plugin.fa().clone(), version),
@@ -28,10 +51,76 @@ impl LegacyVersionFile {
             }
         }
 
-        Ok(Self { toolset, path })
+        let project_root = config_file::default_project_root(&path);
+        Ok(Self {
+            toolset,
+            path,
+            project_root,
+        })
     }
 }
 
+/// a manifest of another package manager that pins a tool's version in one of its fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyManifest {
+    /// `engines.<name>` in package.json
+    PackageJsonEngine(&'static str),
+    /// `project.requires-python` in pyproject.toml
+    PyprojectRequiresPython,
+}
+
+impl LegacyManifest {
+    pub fn filename(&self) -> &'static str {
+        match self {
+            Self::PackageJsonEngine(_) => "package.json",
+            Self::PyprojectRequiresPython => "pyproject.toml",
+        }
+    }
+
+    /// the version pinned by the manifest, None if it doesn't set the field
+    pub fn extract(&self, path: &Path) -> Result<Option<String>> {
+        let body = file::read_to_string(path)?;
+        let range = match self {
+            Self::PackageJsonEngine(name) => {
+                let json: serde_json::Value =
+                    serde_json::from_str(&body).wrap_err("invalid json")?;
+                json.get("engines")
+                    .and_then(|e| e.get(name))
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string())
+            }
+            Self::PyprojectRequiresPython => {
+                let toml: toml::Table = body.parse().wrap_err("invalid toml")?;
+                toml.get("project")
+                    .and_then(|p| p.get("requires-python"))
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string())
+            }
+        };
+        Ok(range.and_then(|r| version_from_range(&r)))
+    }
+}
+
+/// converts a version range that pins a major or minor version like `^18.2.0` to the
+/// version prefix mise should use
+/// e.g.: `^18.2.0` -> `18`, `~18.2.0` -> `18.2`, `~=3.11` -> `3`, `20.x` -> `20`
+/// returns None for open-ended ranges like `>=18` or `<20` since any version could match
+fn version_from_range(range: &str) -> Option<String> {
+    // only the first alternative of e.g.: `^18 || ^20` is used
+    let range = range.split("||").next()?;
+    let caps = regex!(r"^\s*(\^|~=|~|==|=)?\s*v?(\d+(?:\.\d+)*)").captures(range)?;
+    let op = caps.get(1).map_or("", |m| m.as_str());
+    let parts = caps[2].split('.').collect::<Vec<_>>();
+    let keep = match op {
+        "^" if parts[0] == "0" => 2,
+        "^" => 1,
+        "~" => 2,
+        "~=" => parts.len().saturating_sub(1).max(1),
+        _ => parts.len(),
+    };
+    Some(parts[..keep.min(parts.len())].join("."))
+}
+
 impl ConfigFile for LegacyVersionFile {
     fn get_type(&self) -> ConfigFileType {
         ConfigFileType::LegacyVersion
@@ -41,6 +130,14 @@ impl ConfigFile for LegacyVersionFile {
         self.path.as_path()
     }
 
+    fn project_root(&self) -> Option<&Path> {
+        self.project_root.as_deref()
+    }
+
+    fn set_project_root(&mut self, project_root: Option<PathBuf>) {
+        self.project_root = project_root;
+    }
+
     fn remove_plugin(&mut self, _fa: &ForgeArg) {
         unimplemented!()
     }
@@ -61,3 +158,58 @@ impl ConfigFile for LegacyVersionFile {
         &self.toolset
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_from_range() {
+        let v = |r: &str| version_from_range(r);
+        assert_eq!(v("18.2.0").as_deref(), Some("18.2.0"));
+        assert_eq!(v("v20").as_deref(), Some("20"));
+        assert_eq!(v("^18.2.0").as_deref(), Some("18"));
+        assert_eq!(v("^0.9.1").as_deref(), Some("0.9"));
+        assert_eq!(v("~18.2.0").as_deref(), Some("18.2"));
+        assert_eq!(v("~=3.11").as_deref(), Some("3"));
+        assert_eq!(v("==3.12.*").as_deref(), Some("3.12"));
+        assert_eq!(v("20.x").as_deref(), Some("20"));
+        assert_eq!(v("^18 || ^20").as_deref(), Some("18"));
+        assert_eq!(v(">=18.2 <21"), None);
+        assert_eq!(v(">= 3.11"), None);
+        assert_eq!(v(">3.8"), None);
+        assert_eq!(v("<20"), None);
+        assert_eq!(v("*"), None);
+    }
+
+    #[test]
+    fn test_extract() {
+        let dir = tempfile::tempdir().unwrap();
+        let extract = |manifest: LegacyManifest, body: &str| {
+            let path = dir.path().join(manifest.filename());
+            file::write(&path, body).unwrap();
+            manifest.extract(&path)
+        };
+        let node = LegacyManifest::PackageJsonEngine("node");
+        assert_eq!(
+            extract(node, r#"{"engines": {"node": "^18"}}"#).unwrap(),
+            Some("18".into())
+        );
+        assert_eq!(
+            extract(node, r#"{"engines": {"node": ">=18"}}"#).unwrap(),
+            None
+        );
+        assert_eq!(extract(node, r#"{"name": "app"}"#).unwrap(), None);
+        assert!(extract(node, "{").is_err());
+        let python = LegacyManifest::PyprojectRequiresPython;
+        assert_eq!(
+            extract(python, "[project]\nrequires-python = \"~=3.11.0\"\n").unwrap(),
+            Some("3.11".into())
+        );
+        assert_eq!(
+            extract(python, "[project]\nrequires-python = \">=3.8\"\n").unwrap(),
+            None
+        );
+        assert_eq!(extract(python, "[tool.poetry]\n").unwrap(), None);
+    }
+}
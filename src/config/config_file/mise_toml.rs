@@ -6,24 +6,26 @@ use std::sync::Mutex;
 
 use eyre::WrapErr;
 use itertools::Itertools;
+use path_absolutize::Absolutize;
 use serde::de::Visitor;
 use serde::{de, Deserializer};
 use serde_derive::Deserialize;
 use tera::Context as TeraContext;
-use toml_edit::{table, value, Array, Document, Item, Value};
+use toml_edit::{table, value, Array, Document, Item, TableLike, Value};
 use versions::Versioning;
 
 use crate::cli::args::ForgeArg;
 use crate::config::config_file::{trust_check, ConfigFile, ConfigFileType};
 use crate::config::env_directive::EnvDirective;
+use crate::config::merge::MergeStrategy;
 use crate::config::AliasMap;
 use crate::file::{create_dir_all, display_path};
-use crate::task::Task;
+use crate::shorthands::Shorthands;
+use crate::task::{is_remote_url, Task};
 use crate::tera::{get_tera, BASE_CONTEXT};
 use crate::toolset::{
     ToolSource, ToolVersionList, ToolVersionOptions, ToolVersionRequest, Toolset,
 };
-use crate::ui::style;
 use crate::{dirs, file, parse_error};
 
 #[derive(Default, Deserialize)]
@@ -43,12 +45,16 @@ pub struct MiseToml {
     env: EnvList,
     #[serde(default, deserialize_with = "deserialize_arr")]
     env_path: Vec<PathBuf>,
+    #[serde(default, deserialize_with = "deserialize_arr")]
+    include: Vec<PathBuf>,
     #[serde(default, deserialize_with = "deserialize_alias")]
     alias: AliasMap,
     #[serde(skip)]
     doc: Document,
     #[serde(default)]
     plugins: HashMap<String, String>,
+    #[serde(default)]
+    shorthands: Shorthands,
     #[serde(skip)]
     tasks: Vec<Task>,
     #[serde(skip)]
@@ -57,6 +63,9 @@ pub struct MiseToml {
     project_root: Option<PathBuf>,
     #[serde(skip)]
     config_root: PathBuf,
+    /// dotted paths of keys that aren't part of the schema, e.g.: `task.build`
+    #[serde(skip)]
+    unknown_keys: Vec<String>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -114,8 +123,10 @@ impl MiseToml {
         self.env = cfg.env;
         self.env_file = cfg.env_file;
         self.env_path = cfg.env_path;
+        self.include = cfg.include;
         self.min_version = cfg.min_version;
         self.plugins = cfg.plugins;
+        self.shorthands = cfg.shorthands;
 
         // TODO: right now some things are parsed with serde (above) and some not (below) everything
         // should be moved to serde eventually
@@ -125,16 +136,18 @@ impl MiseToml {
             match k {
                 "tools" => self.toolset = self.parse_toolset(k, v)?,
                 "tasks" => self.tasks = self.parse_tasks(k, v)?,
-                "alias" | "dotenv" | "env_file" | "env_path" | "min_version" | "settings"
-                | "env" | "plugins" => {}
-                _ => bail!("unknown key: {}", style::ered(k)),
+                "env" => self.unknown_keys.extend(unknown_env_directives(k, v)),
+                "alias" => self.unknown_keys.extend(unknown_aliases(k, v)),
+                "dotenv" | "env_file" | "env_path" | "include" | "min_version" | "settings"
+                | "plugins" | "shorthands" => {}
+                _ => self.unknown_keys.push(k.to_string()),
             }
         }
         self.doc = doc;
         Ok(())
     }
 
-    fn parse_tasks(&self, key: &str, v: &Item) -> eyre::Result<Vec<Task>> {
+    fn parse_tasks(&mut self, key: &str, v: &Item) -> eyre::Result<Vec<Task>> {
         match v.as_table_like() {
             Some(table) => {
                 let mut tasks = Vec::new();
@@ -150,7 +163,7 @@ impl MiseToml {
         }
     }
 
-    fn parse_task(&self, key: &str, v: &Item, name: &str) -> eyre::Result<Task> {
+    fn parse_task(&mut self, key: &str, v: &Item, name: &str) -> eyre::Result<Task> {
         let mut task = Task::new(name.into(), self.path.clone());
         if v.as_str().is_some() {
             task.run = self.parse_string_or_array(key, v)?;
@@ -173,14 +186,21 @@ impl MiseToml {
                         "depends" => task.depends = self.parse_string_array(&key, v)?,
                         "description" => task.description = self.parse_string(&key, v)?,
                         "env" => task.env = self.parse_hashmap(&key, v)?,
-                        "file" => task.file = Some(self.parse_path(&key, v)?),
+                        "checksum" => task.checksum = Some(self.parse_string(&key, v)?),
+                        "file" => {
+                            let file = self.parse_path(&key, v)?;
+                            match file.to_str().filter(|f| is_remote_url(f)) {
+                                Some(url) => task.remote = Some(url.to_string()),
+                                None => task.file = Some(file),
+                            }
+                        }
                         "hide" => task.hide = self.parse_bool(&key, v)?,
                         "dir" => task.dir = Some(self.parse_path(&key, v)?),
                         "outputs" => task.outputs = self.parse_string_array(&key, v)?,
                         "raw" => task.raw = self.parse_bool(&key, v)?,
                         // "script" => task.script = Some(self.parse_string_tmpl(&key, v)?),
                         "sources" => task.sources = self.parse_string_array(&key, v)?,
-                        _ => parse_error!(key, v, "task property"),
+                        _ => self.unknown_keys.push(key),
                     }
                 }
                 Ok(task)
@@ -481,6 +501,10 @@ impl ConfigFile for MiseToml {
         self.project_root.as_deref()
     }
 
+    fn set_project_root(&mut self, project_root: Option<PathBuf>) {
+        self.project_root = project_root;
+    }
+
     fn plugins(&self) -> HashMap<String, String> {
         self.plugins.clone()
     }
@@ -508,6 +532,25 @@ impl ConfigFile for MiseToml {
         self.tasks.iter().collect()
     }
 
+    fn unknown_keys(&self) -> Vec<&str> {
+        self.unknown_keys.iter().map(|k| k.as_str()).collect()
+    }
+
+    fn includes(&self) -> Vec<PathBuf> {
+        let dir = self.path.parent().unwrap_or(Path::new("/"));
+        self.include
+            .iter()
+            .map(|p| match p.strip_prefix("~") {
+                Ok(p) => dirs::HOME.join(p),
+                Err(_) => dir.join(p),
+            })
+            .map(|p| match p.absolutize() {
+                Ok(abs) => abs.to_path_buf(),
+                Err(_) => p,
+            })
+            .collect()
+    }
+
     fn remove_plugin(&mut self, fa: &ForgeArg) {
         self.toolset.versions.shift_remove(fa);
         if let Some(tools) = self.doc.get_mut("tools") {
@@ -564,6 +607,10 @@ impl ConfigFile for MiseToml {
     fn aliases(&self) -> AliasMap {
         self.alias.clone()
     }
+
+    fn shorthands(&self) -> Shorthands {
+        self.shorthands.clone()
+    }
 }
 
 impl Debug for MiseToml {
@@ -578,6 +625,9 @@ impl Debug for MiseToml {
         if !self.env_file.is_empty() {
             d.field("env_file", &self.env_file);
         }
+        if !self.include.is_empty() {
+            d.field("include", &self.include);
+        }
         let env = self.env_entries();
         if !env.is_empty() {
             d.field("env", &env);
@@ -602,13 +652,16 @@ impl Clone for MiseToml {
             env_file: self.env_file.clone(),
             env: self.env.clone(),
             env_path: self.env_path.clone(),
+            include: self.include.clone(),
             alias: self.alias.clone(),
             doc: self.doc.clone(),
             plugins: self.plugins.clone(),
+            shorthands: self.shorthands.clone(),
             tasks: self.tasks.clone(),
             is_trusted: Mutex::new(*self.is_trusted.lock().unwrap()),
             project_root: self.project_root.clone(),
             config_root: self.config_root.clone(),
+            unknown_keys: self.unknown_keys.clone(),
         }
     }
 }
@@ -629,7 +682,7 @@ where
     }
 }
 
-fn deserialize_arr<'de, D, T>(deserializer: D) -> eyre::Result<Vec<T>, D::Error>
+pub fn deserialize_arr<'de, D, T>(deserializer: D) -> eyre::Result<Vec<T>, D::Error>
 where
     D: Deserializer<'de>,
     T: FromStr,
@@ -701,8 +754,8 @@ impl<'de> de::Deserialize<'de> for EnvList {
                 while let Some(key) = map.next_key::<String>()? {
                     match key.as_str() {
                         "_" | "mise" => {
+                            // unknown directives are reported by `unknown_env_directives`
                             #[derive(Deserialize)]
-                            #[serde(deny_unknown_fields)]
                             struct EnvDirectives {
                                 #[serde(default, deserialize_with = "deserialize_arr")]
                                 path: Vec<PathBuf>,
@@ -710,8 +763,15 @@ impl<'de> de::Deserialize<'de> for EnvList {
                                 file: Vec<PathBuf>,
                                 #[serde(default, deserialize_with = "deserialize_arr")]
                                 source: Vec<PathBuf>,
+                                #[serde(default, deserialize_with = "deserialize_arr")]
+                                unset: Vec<String>,
+                                #[serde(default)]
+                                merge: Option<MergeStrategy>,
                             }
                             let directives = map.next_value::<EnvDirectives>()?;
+                            if let Some(strategy) = directives.merge {
+                                env.push(EnvDirective::Merge(strategy));
+                            }
                             // TODO: parse these in the order they're defined somehow
                             for path in directives.path {
                                 env.push(EnvDirective::Path(path));
@@ -722,6 +782,9 @@ impl<'de> de::Deserialize<'de> for EnvList {
                             for source in directives.source {
                                 env.push(EnvDirective::Source(source));
                             }
+                            for key in directives.unset {
+                                env.push(EnvDirective::Rm(key));
+                            }
                         }
                         _ => {
                             enum Val {
@@ -801,6 +864,48 @@ impl<'de> de::Deserialize<'de> for EnvList {
     }
 }
 
+/// keys of `_` in `[env]` that aren't env directives, e.g.: `env._.paths`
+fn unknown_env_directives(key: &str, v: &Item) -> Vec<String> {
+    let tables = match v {
+        Item::ArrayOfTables(tables) => tables.iter().map(|t| t as &dyn TableLike).collect(),
+        v => v.as_table_like().into_iter().collect_vec(),
+    };
+    tables
+        .into_iter()
+        .flat_map(|table| {
+            ["_", "mise"]
+                .into_iter()
+                .filter_map(|k| Some((k, table.get(k)?.as_table_like()?)))
+        })
+        .flat_map(|(k, directives)| {
+            directives
+                .iter()
+                .filter(|(d, _)| !["path", "file", "source", "unset", "merge"].contains(d))
+                .map(move |(d, _)| format!("{key}.{k}.{d}"))
+        })
+        .collect()
+}
+
+/// entries of `[alias]` that aren't a table of strings mapping an alias to a version
+fn unknown_aliases(key: &str, v: &Item) -> Vec<String> {
+    let Some(table) = v.as_table_like() else {
+        return vec![];
+    };
+    let mut unknown = vec![];
+    for (tool, aliases) in table.iter() {
+        match aliases.as_table_like() {
+            Some(aliases) => unknown.extend(
+                aliases
+                    .iter()
+                    .filter(|(_, v)| !v.is_str())
+                    .map(|(from, _)| format!("{key}.{tool}.{from}")),
+            ),
+            None => unknown.push(format!("{key}.{tool}")),
+        }
+    }
+    unknown
+}
+
 fn deserialize_alias<'de, D>(deserializer: D) -> Result<AliasMap, D::Error>
 where
     D: Deserializer<'de>,
@@ -821,8 +926,12 @@ where
             while let Some(plugin) = map.next_key::<String>()? {
                 let fa: ForgeArg = plugin.parse().map_err(de::Error::custom)?;
                 let plugin_aliases = aliases.entry(fa).or_default();
-                for (from, to) in map.next_value::<BTreeMap<String, String>>()? {
-                    plugin_aliases.insert(from, to);
+                // values that aren't strings are reported by `unknown_aliases`
+                let values = map.next_value::<Option<BTreeMap<String, toml::Value>>>();
+                for (from, to) in values.ok().flatten().unwrap_or_default() {
+                    if let toml::Value::String(to) = to {
+                        plugin_aliases.insert(from, to);
+                    }
                 }
             }
             Ok(aliases)
@@ -974,6 +1083,35 @@ mod tests {
         assert_debug_snapshot!(env, @r###""dotenv .env\ndotenv .env2""###);
     }
 
+    #[test]
+    fn test_env_unset() {
+        let env = parse_env(formatdoc! {r#"
+            [env]
+            _.unset = "GOFLAGS"
+            "#});
+        assert_debug_snapshot!(env, @r###""unset GOFLAGS""###);
+
+        let env = parse_env(formatdoc! {r#"
+            [env]
+            _.unset = ["GOFLAGS", "NODE_OPTIONS"]
+            FOO = false
+            "#});
+        assert_debug_snapshot!(env, @r###""unset GOFLAGS\nunset NODE_OPTIONS\nunset FOO""###);
+    }
+
+    #[test]
+    fn test_env_merge() {
+        let toml = formatdoc! {r#"
+            [env]
+            _.merge = "replace"
+            _.path = "./bin"
+            "#};
+        assert_snapshot!(parse_env(toml), @r###"
+        merge replace
+        path_add ./bin
+        "###);
+    }
+
     #[test]
     fn test_set_alias() {
         let mut cf = MiseToml::init(PathBuf::from("/tmp/.mise.toml").as_path());
@@ -1055,13 +1193,39 @@ mod tests {
     }
 
     #[test]
-    fn test_fail_with_unknown_key() {
+    fn test_unknown_keys() {
         let mut cf = MiseToml::init(PathBuf::from("/tmp/.mise.toml").as_path());
-        let _ = cf
-            .parse(&formatdoc! {r#"
+        cf.parse(&formatdoc! {r#"
         invalid_key = true
+        [task.build]
+        run = "echo build"
+        [tasks.test]
+        run = "echo test"
+        sorces = ["src/**"]
+        [env]
+        ANY_NAME = "ok"
+        _.file = ".env"
+        _.paths = ["bin"]
+        [alias]
+        tiny = "3"
+        [alias.dummy]
+        lts = "1.0"
+        old = 1
         "#})
-            .unwrap_err();
+            .unwrap();
+        assert_eq!(
+            cf.unknown_keys(),
+            vec![
+                "invalid_key",
+                "task",
+                "tasks.test.sorces",
+                "env._.paths",
+                "alias.tiny",
+                "alias.dummy.old"
+            ]
+        );
+        assert_eq!(cf.alias[&"dummy".parse().unwrap()].len(), 1);
+        assert_eq!(cf.tasks[0].run, vec!["echo test"]);
     }
 
     #[test]
@@ -1124,6 +1288,27 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn test_includes() {
+        let cf = parse(formatdoc! {r#"
+            include = ["../shared/mise.toml", "./local.toml", "/etc/mise.toml", "~/mise.toml"]
+            "#});
+        assert_eq!(
+            cf.includes(),
+            vec![
+                PathBuf::from("/shared/mise.toml"),
+                PathBuf::from("/tmp/local.toml"),
+                PathBuf::from("/etc/mise.toml"),
+                dirs::HOME.join("mise.toml"),
+            ]
+        );
+
+        let cf = parse(formatdoc! {r#"
+            include = "shared.toml"
+            "#});
+        assert_eq!(cf.includes(), vec![PathBuf::from("/tmp/shared.toml")]);
+    }
+
     fn parse(s: String) -> MiseToml {
         let mut cf = MiseToml::init(PathBuf::from("/tmp/.mise.toml").as_path());
         cf.parse(&s).unwrap();
@@ -18,6 +18,7 @@ use crate::config::{AliasMap, Settings};
 use crate::errors::Error::UntrustedConfig;
 use crate::file::display_path;
 use crate::hash::{file_hash_sha256, hash_to_str};
+use crate::shorthands::Shorthands;
 use crate::task::Task;
 use crate::toolset::{ToolVersionList, Toolset};
 use crate::ui::{prompt, style};
@@ -45,21 +46,9 @@ pub trait ConfigFile: Debug + Send + Sync {
     /// if it's a global/system config, returns None
     /// files like ~/src/foo/.mise/config.toml will return ~/src/foo
     /// and ~/src/foo/.mise.config.toml will return None
-    fn project_root(&self) -> Option<&Path> {
-        let p = self.get_path();
-        if *env::MISE_GLOBAL_CONFIG_FILE == p {
-            return None;
-        }
-        match p.parent() {
-            Some(dir) => match dir {
-                dir if dir.starts_with(*dirs::CONFIG) => None,
-                dir if dir.starts_with(*dirs::SYSTEM) => None,
-                dir if dir == *dirs::HOME => None,
-                dir => Some(dir),
-            },
-            None => None,
-        }
-    }
+    fn project_root(&self) -> Option<&Path>;
+    /// included files use the project root of the file that includes them instead of their own
+    fn set_project_root(&mut self, project_root: Option<PathBuf>);
     fn plugins(&self) -> HashMap<String, String> {
         Default::default()
     }
@@ -69,6 +58,15 @@ pub trait ConfigFile: Debug + Send + Sync {
     fn tasks(&self) -> Vec<&Task> {
         Default::default()
     }
+    /// dotted paths of keys that were ignored because they aren't part of the schema
+    fn unknown_keys(&self) -> Vec<&str> {
+        Default::default()
+    }
+    /// other config files to load along with this one
+    /// paths are absolute, relative paths are resolved against this file's directory
+    fn includes(&self) -> Vec<PathBuf> {
+        Default::default()
+    }
     fn remove_plugin(&mut self, _fa: &ForgeArg);
     fn replace_versions(&mut self, fa: &ForgeArg, versions: &[String]);
     fn save(&self) -> Result<()>;
@@ -77,6 +75,26 @@ pub trait ConfigFile: Debug + Send + Sync {
     fn aliases(&self) -> AliasMap {
         Default::default()
     }
+    /// plugin repository urls that take precedence over the built-in shorthands
+    fn shorthands(&self) -> Shorthands {
+        Default::default()
+    }
+}
+
+/// the project root of a config file that isn't a .mise.toml which has its own rules
+pub fn default_project_root(path: &Path) -> Option<PathBuf> {
+    if *env::MISE_GLOBAL_CONFIG_FILE == path {
+        return None;
+    }
+    match path.parent() {
+        Some(dir) => match dir {
+            dir if dir.starts_with(*dirs::CONFIG) => None,
+            dir if dir.starts_with(*dirs::SYSTEM) => None,
+            dir if dir == *dirs::HOME => None,
+            dir => Some(dir.to_path_buf()),
+        },
+        None => None,
+    }
 }
 
 impl dyn ConfigFile {
@@ -27,6 +27,7 @@ pub struct ToolVersions {
     pre: String,
     plugins: IndexMap<ForgeArg, ToolVersionPlugin>,
     toolset: Toolset,
+    project_root: Option<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -44,6 +45,7 @@ impl ToolVersions {
             context,
             toolset: Toolset::new(ToolSource::ToolVersions(filename.to_path_buf())),
             path: filename.to_path_buf(),
+            project_root: config_file::default_project_root(filename),
             ..Default::default()
         }
     }
@@ -159,6 +161,14 @@ impl ConfigFile for ToolVersions {
         self.path.as_path()
     }
 
+    fn project_root(&self) -> Option<&Path> {
+        self.project_root.as_deref()
+    }
+
+    fn set_project_root(&mut self, project_root: Option<PathBuf>) {
+        self.project_root = project_root;
+    }
+
     fn remove_plugin(&mut self, fa: &ForgeArg) {
         self.plugins.shift_remove(fa);
     }
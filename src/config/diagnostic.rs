@@ -0,0 +1,53 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use strum::Display;
+
+use crate::file::display_path;
+
+/// a problem found in a config file by `Config::diagnose`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub path: PathBuf,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum Severity {
+    /// reported by `mise doctor` but does not prevent the config from loading
+    Warning,
+    /// prevents the config from loading
+    Error,
+}
+
+impl Diagnostic {
+    pub fn warning(path: &Path, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            path: path.to_path_buf(),
+            message: message.into(),
+        }
+    }
+
+    pub fn error(path: &Path, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            path: path.to_path_buf(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}: {}",
+            self.severity,
+            display_path(&self.path),
+            self.message
+        )
+    }
+}
@@ -1,12 +1,16 @@
 use crate::config::config_file::trust_check;
+use crate::config::merge::{merge_list, MergeStrategy};
 use crate::config::Settings;
-use crate::dirs;
 use crate::env_diff::{EnvDiff, EnvDiffOperation};
+use crate::errors::Error::UntrustedConfig;
 use crate::file::display_path;
 use crate::tera::{get_tera, BASE_CONTEXT};
+use crate::{dirs, sops};
 use eyre::Context;
 use indexmap::IndexMap;
-use std::collections::{BTreeSet, HashMap};
+use path_absolutize::Absolutize;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
 use std::path::{Path, PathBuf};
 
@@ -22,6 +26,8 @@ pub enum EnvDirective {
     Path(PathBuf),
     /// run a bash script and apply the resulting env diff
     Source(PathBuf),
+    /// how this config file's paths combine with the ones from lower-precedence config files
+    Merge(MergeStrategy),
 }
 
 impl From<(String, String)> for EnvDirective {
@@ -44,107 +50,162 @@ impl Display for EnvDirective {
             EnvDirective::File(path) => write!(f, "dotenv {}", display_path(path)),
             EnvDirective::Path(path) => write!(f, "path_add {}", display_path(path)),
             EnvDirective::Source(path) => write!(f, "source {}", display_path(path)),
+            EnvDirective::Merge(strategy) => write!(f, "merge {strategy}"),
         }
     }
 }
 
-#[derive(Debug)]
+/// resolves a path from an env directive relative to the directory of its config file
+pub fn normalize_path(config_root: &Path, s: &str) -> PathBuf {
+    let s = s.strip_prefix("./").unwrap_or(s);
+    match s.strip_prefix("~/") {
+        Some(s) => dirs::HOME.join(s),
+        None if s.starts_with('/') => PathBuf::from(s),
+        None => config_root.join(s),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnvResults {
     pub env: IndexMap<String, (String, PathBuf)>,
-    pub env_remove: BTreeSet<String>,
+    /// keys removed by an unset directive and the config file that removed them
+    pub env_remove: BTreeMap<String, PathBuf>,
     pub env_files: Vec<PathBuf>,
     pub env_paths: Vec<PathBuf>,
     pub env_scripts: Vec<PathBuf>,
+    /// entries that failed to resolve and the config file they came from
+    pub errors: Vec<(PathBuf, String)>,
 }
 
 impl EnvResults {
     pub fn resolve(
         initial: &HashMap<String, String>,
         input: Vec<(EnvDirective, PathBuf)>,
+        project_root: Option<&Path>,
     ) -> eyre::Result<Self> {
         let settings = Settings::get();
         let mut ctx = BASE_CONTEXT.clone();
+        if let Some(project_root) = project_root {
+            ctx.insert("project_root", &project_root.absolutize()?);
+        }
         let mut env = initial
             .iter()
             .map(|(k, v)| (k.clone(), (v.clone(), None)))
             .collect::<IndexMap<_, _>>();
         let mut r = Self {
             env: Default::default(),
-            env_remove: BTreeSet::new(),
+            env_remove: BTreeMap::new(),
             env_files: Vec::new(),
             env_paths: Vec::new(),
             env_scripts: Vec::new(),
+            errors: Vec::new(),
         };
+        let mut paths: Vec<(PathBuf, Vec<PathBuf>)> = Vec::new();
+        let mut strategies = HashMap::new();
         for (directive, source) in input {
-            let config_root = source.parent().unwrap();
-            ctx.insert("config_root", config_root);
+            // each entry sees the directory of its own config file
+            let config_root = source.absolutize()?.parent().unwrap().to_path_buf();
+            ctx.insert("config_root", &config_root);
             let env_vars = env
                 .iter()
                 .map(|(k, (v, _))| (k.clone(), v.clone()))
                 .collect::<HashMap<_, _>>();
             ctx.insert("env", &env_vars);
-            let normalize_path = |s: String| {
-                let s = s.strip_prefix("./").unwrap_or(&s);
-                match s.strip_prefix("~/") {
-                    Some(s) => dirs::HOME.join(s),
-                    None if s.starts_with('/') => PathBuf::from(s),
-                    None => config_root.join(s),
-                }
-            };
-            match directive {
-                EnvDirective::Val(k, v) => {
-                    let v = r.parse_template(&ctx, &source, &v)?;
-                    r.env_remove.remove(&k);
-                    env.insert(k, (v, Some(source.clone())));
-                }
-                EnvDirective::Rm(k) => {
-                    env.shift_remove(&k);
-                    r.env_remove.insert(k);
-                }
-                EnvDirective::Path(input) => {
-                    let s = r.parse_template(&ctx, &source, input.to_string_lossy().as_ref())?;
-                    let p = normalize_path(s);
-                    r.env_paths.push(p.clone());
-                }
-                EnvDirective::File(input) => {
+            // trust and experimental errors always propagate, only resolving an entry can fail
+            match &directive {
+                EnvDirective::File(_) => trust_check(&source)?,
+                EnvDirective::Source(_) => {
+                    settings.ensure_experimental()?;
                     trust_check(&source)?;
-                    let s = r.parse_template(&ctx, &source, input.to_string_lossy().as_ref())?;
-                    let p = normalize_path(s);
-                    r.env_files.push(p.clone());
-                    let errfn = || eyre!("failed to parse dotenv file: {}", display_path(&p));
-                    for item in dotenvy::from_path_iter(&p).wrap_err_with(errfn)? {
-                        let (k, v) = item.wrap_err_with(errfn)?;
+                }
+                _ => {}
+            }
+            // a failed entry is skipped so it doesn't prevent later entries from resolving
+            let resolved = (|| -> eyre::Result<()> {
+                let normalize_path = |s: String| normalize_path(&config_root, &s);
+                match directive {
+                    EnvDirective::Val(k, v) => {
+                        let v = r.parse_template(&ctx, &source, &v)?;
                         r.env_remove.remove(&k);
-                        env.insert(k, (v, Some(p.clone())));
+                        env.insert(k, (v, Some(source.clone())));
                     }
-                }
-                EnvDirective::Source(input) => {
-                    settings.ensure_experimental()?;
-                    trust_check(&source)?;
-                    let s = r.parse_template(&ctx, &source, input.to_string_lossy().as_ref())?;
-                    let p = normalize_path(s);
-                    r.env_scripts.push(p.clone());
-                    let env_diff = EnvDiff::from_bash_script(&p, env_vars.clone())?;
-                    for p in env_diff.to_patches() {
-                        match p {
-                            EnvDiffOperation::Add(k, v) | EnvDiffOperation::Change(k, v) => {
+                    EnvDirective::Rm(k) => {
+                        env.shift_remove(&k);
+                        r.env_remove.insert(k, source.clone());
+                    }
+                    EnvDirective::Path(input) => {
+                        let s =
+                            r.parse_template(&ctx, &source, input.to_string_lossy().as_ref())?;
+                        let p = normalize_path(s);
+                        match paths.last_mut() {
+                            Some((s, ps)) if *s == source => ps.push(p),
+                            _ => paths.push((source.clone(), vec![p])),
+                        }
+                    }
+                    EnvDirective::Merge(strategy) => {
+                        strategies.insert(source.clone(), strategy);
+                    }
+                    EnvDirective::File(input) => {
+                        let s =
+                            r.parse_template(&ctx, &source, input.to_string_lossy().as_ref())?;
+                        let p = normalize_path(s);
+                        r.env_files.push(p.clone());
+                        if sops::is_encrypted(&p) {
+                            let items = sops::decrypt_env(&p).wrap_err_with(|| {
+                                eyre!("failed to decrypt env_file {}", display_path(&p))
+                            })?;
+                            for (k, v) in items {
                                 r.env_remove.remove(&k);
-                                env.insert(k.clone(), (v.clone(), Some(source.clone())));
+                                env.insert(k, (v, Some(p.clone())));
                             }
-                            EnvDiffOperation::Remove(k) => {
-                                env.shift_remove(&k);
-                                r.env_remove.insert(k);
+                            return Ok(());
+                        }
+                        let errfn = || eyre!("failed to parse dotenv file: {}", display_path(&p));
+                        for item in dotenvy::from_path_iter(&p).wrap_err_with(errfn)? {
+                            let (k, v) = item.wrap_err_with(errfn)?;
+                            r.env_remove.remove(&k);
+                            env.insert(k, (v, Some(p.clone())));
+                        }
+                    }
+                    EnvDirective::Source(input) => {
+                        let s =
+                            r.parse_template(&ctx, &source, input.to_string_lossy().as_ref())?;
+                        let p = normalize_path(s);
+                        r.env_scripts.push(p.clone());
+                        let env_diff = EnvDiff::from_bash_script(&p, env_vars.clone())?;
+                        for p in env_diff.to_patches() {
+                            match p {
+                                EnvDiffOperation::Add(k, v) | EnvDiffOperation::Change(k, v) => {
+                                    r.env_remove.remove(&k);
+                                    env.insert(k.clone(), (v.clone(), Some(source.clone())));
+                                }
+                                EnvDiffOperation::Remove(k) => {
+                                    env.shift_remove(&k);
+                                    r.env_remove.insert(k, source.clone());
+                                }
                             }
                         }
                     }
+                };
+                Ok(())
+            })();
+            if let Err(err) = resolved {
+                if settings.strict_env || matches!(err.downcast_ref(), Some(UntrustedConfig())) {
+                    return Err(err);
                 }
-            };
+                r.errors.push((source, format!("{err:#}")));
+            }
         }
         for (k, (v, source)) in env {
             if let Some(source) = source {
                 r.env.insert(k, (v, source));
             }
         }
+        let default_strategy = settings.merge_strategy;
+        for (source, ps) in paths {
+            let strategy = strategies.get(&source).copied().unwrap_or(default_strategy);
+            merge_list(strategy, &mut r.env_paths, ps);
+        }
         Ok(r)
     }
 
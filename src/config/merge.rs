@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+use serde_derive::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+/// how list-valued entries (env paths, alias tables) from a config file are combined with the
+/// ones from lower-precedence config files
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Display, EnumString, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// add to the entries from lower-precedence config files
+    #[default]
+    Append,
+    /// discard the entries from lower-precedence config files
+    Replace,
+}
+
+/// merges `other` from a higher-precedence config file into `base`
+/// duplicates are removed after appending, keeping the first occurrence
+pub fn merge_list<T: Clone + Eq + std::hash::Hash>(
+    strategy: MergeStrategy,
+    base: &mut Vec<T>,
+    other: Vec<T>,
+) {
+    match strategy {
+        MergeStrategy::Append => base.extend(other),
+        MergeStrategy::Replace => *base = other,
+    }
+    *base = base.drain(..).unique().collect();
+}
+
+/// merges `other` from a higher-precedence config file into `base`, `other` wins on conflicts
+pub fn merge_map<K: Ord, V>(
+    strategy: MergeStrategy,
+    base: &mut BTreeMap<K, V>,
+    other: BTreeMap<K, V>,
+) {
+    match strategy {
+        MergeStrategy::Append => base.extend(other),
+        MergeStrategy::Replace => *base = other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_list() {
+        let mut base = vec!["/a", "/b"];
+        merge_list(MergeStrategy::Append, &mut base, vec!["/c", "/a"]);
+        assert_eq!(base, vec!["/a", "/b", "/c"]);
+        merge_list(MergeStrategy::Replace, &mut base, vec!["/d", "/d"]);
+        assert_eq!(base, vec!["/d"]);
+    }
+
+    #[test]
+    fn test_merge_map() {
+        let mut base = BTreeMap::from([("lts", "18"), ("old", "16")]);
+        merge_map(
+            MergeStrategy::Append,
+            &mut base,
+            BTreeMap::from([("lts", "20")]),
+        );
+        assert_eq!(base, BTreeMap::from([("lts", "20"), ("old", "16")]));
+        merge_map(
+            MergeStrategy::Replace,
+            &mut base,
+            BTreeMap::from([("new", "22")]),
+        );
+        assert_eq!(base, BTreeMap::from([("new", "22")]));
+    }
+}
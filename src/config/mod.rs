@@ -1,4 +1,6 @@
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::env::split_paths;
 use std::fmt::{Debug, Formatter};
 use std::iter::once;
 use std::path::{Path, PathBuf};
@@ -6,41 +8,53 @@ use std::sync::{Arc, RwLock};
 
 use either::Either;
 use eyre::{Context, Result};
+use indexmap::map::Entry;
 use indexmap::IndexMap;
 use itertools::Itertools;
 use once_cell::sync::{Lazy, OnceCell};
+use path_absolutize::Absolutize;
 use rayon::prelude::*;
+use serde_derive::Serialize;
 
 pub use settings::Settings;
 
+use crate::cache::CacheManager;
 use crate::cli::args::ForgeArg;
 use crate::cli::version;
 use crate::config::config_file::legacy_version::LegacyVersionFile;
 use crate::config::config_file::mise_toml::MiseToml;
-use crate::config::config_file::ConfigFile;
-use crate::config::env_directive::EnvResults;
+use crate::config::config_file::{ConfigFile, ConfigFileType};
+use crate::config::diagnostic::{Diagnostic, Severity};
+use crate::config::env_directive::{normalize_path, EnvDirective, EnvResults};
+use crate::config::merge::merge_map;
 use crate::config::tracking::Tracker;
 use crate::file::display_path;
-use crate::forge::Forge;
+use crate::forge::{AForge, Forge};
+use crate::hash::hash_to_str;
 use crate::shorthands::{get_shorthands, Shorthands};
-use crate::task::Task;
+use crate::task::{check_cycles, Task};
+use crate::toolset::{ToolSource, ToolVersionRequest, ToolsetBuilder};
 use crate::ui::style;
-use crate::{dirs, env, file, forge};
+use crate::{dirs, duration, env, file, forge, sops};
 
 pub mod config_file;
+pub mod diagnostic;
 mod env_directive;
+pub mod merge;
 pub mod settings;
 mod tracking;
 
 type AliasMap = BTreeMap<ForgeArg, BTreeMap<String, String>>;
 type ConfigMap = IndexMap<PathBuf, Box<dyn ConfigFile>>;
 type EnvWithSources = IndexMap<String, (String, PathBuf)>;
+type EnvJson = IndexMap<String, EnvJsonEntry>;
 
 #[derive(Default)]
 pub struct Config {
     pub aliases: AliasMap,
     pub config_files: ConfigMap,
     pub project_root: Option<PathBuf>,
+    dir: Option<PathBuf>,
     env: OnceCell<EnvResults>,
     env_with_sources: OnceCell<EnvWithSources>,
     all_aliases: OnceCell<AliasMap>,
@@ -50,6 +64,41 @@ pub struct Config {
     tasks_with_aliases: OnceCell<HashMap<String, Task>>,
 }
 
+/// a resolved env var as it appears in `mise set --json`
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvJsonEntry {
+    pub value: String,
+    /// absolute path of the config or dotenv file that set the variable
+    pub source: PathBuf,
+    #[serde(rename = "type")]
+    pub kind: EnvJsonKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvJsonKind {
+    /// an `[env]` entry of a config file
+    Config,
+    /// a var read from a dotenv file
+    EnvFile,
+}
+
+/// how the config's env changes the pristine environment, see `Config::env_diff`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ConfigEnvDiff {
+    /// vars that aren't set in the pristine env
+    pub added: IndexMap<String, String>,
+    /// vars with a different value than in the pristine env, as (old, new)
+    pub changed: IndexMap<String, (String, String)>,
+    /// vars removed by an unset directive and their pristine value
+    pub removed: IndexMap<String, String>,
+    /// directories put in front of the pristine PATH, in order. If PATH is unset this is the
+    /// whole new PATH
+    pub path_added: Vec<PathBuf>,
+    /// directories of the pristine PATH that are dropped because PATH is unset, in order
+    pub path_removed: Vec<PathBuf>,
+}
+
 static CONFIG: RwLock<Option<Arc<Config>>> = RwLock::new(None);
 
 impl Config {
@@ -65,29 +114,48 @@ impl Config {
         Ok(config)
     }
     pub fn load() -> Result<Self> {
+        // The current directory is not always available, e.g.
+        // when a directory was deleted or inside FUSE mounts.
+        match env::current_dir() {
+            Ok(dir) => Self::load_from_dir(&dir),
+            Err(err) => {
+                debug!("error getting current dir: {err}");
+                Self::load_from(None)
+            }
+        }
+    }
+    /// loads config files by searching up from `dir` instead of the current directory
+    /// this returns a new config and does not touch the one returned by `Config::get()`
+    /// settings are process-wide so `[settings]` still come from the config files of the
+    /// current directory, not the ones found from `dir`
+    pub fn load_from_dir(dir: &Path) -> Result<Self> {
+        Self::load_from(Some(dir.to_path_buf()))
+    }
+    fn load_from(dir: Option<PathBuf>) -> Result<Self> {
         let settings = Settings::try_get()?;
         trace!("Settings: {:#?}", settings);
 
         let legacy_files = load_legacy_files(&settings);
-        let config_filenames = legacy_files
-            .keys()
-            .chain(DEFAULT_CONFIG_FILENAMES.iter())
-            .cloned()
-            .collect_vec();
-        let config_paths = load_config_paths(&config_filenames);
-        let config_files = load_all_config_files(&config_paths, &legacy_files)?;
+        let config_paths = find_config_paths(dir.as_deref(), &settings, &legacy_files);
+        let config_files = load_all_config_files(
+            &config_paths,
+            &legacy_files,
+            &settings.override_config_filename,
+            settings.skip_invalid_config_files,
+        )?;
 
         let repo_urls = config_files.values().flat_map(|cf| cf.plugins()).collect();
 
         let config = Self {
             env: OnceCell::new(),
             env_with_sources: OnceCell::new(),
-            aliases: load_aliases(&config_files),
+            aliases: load_aliases(&settings, &config_files),
             all_aliases: OnceCell::new(),
             shorthands: OnceCell::new(),
             tasks: OnceCell::new(),
             tasks_with_aliases: OnceCell::new(),
             project_root: get_project_root(&config_files),
+            dir,
             config_files,
             repo_urls,
         };
@@ -109,32 +177,110 @@ impl Config {
         self.env_with_sources.get_or_try_init(|| {
             let mut env = self.env_results()?.env.clone();
             let settings = Settings::get();
-            if let Some(env_file) = &settings.env_file {
-                match dotenvy::from_filename_iter(env_file) {
-                    Ok(iter) => {
-                        for item in iter {
-                            let (k, v) = item.unwrap_or_else(|err| {
-                                warn!("env_file: {err}");
-                                Default::default()
-                            });
-                            env.insert(k, (v, env_file.clone()));
+            // later files override earlier ones
+            for env_file in &settings.env_file {
+                let path = match find_env_file(self.dir.as_deref(), env_file) {
+                    Some(path) => path,
+                    None => {
+                        trace!("env_file: {} not found", display_path(env_file));
+                        continue;
+                    }
+                };
+                let items = if sops::is_encrypted(&path) {
+                    sops::decrypt_env(&path)
+                        .map_err(|err| {
+                            warn!(
+                                "failed to decrypt env_file {}: {err:#}",
+                                display_path(&path)
+                            )
+                        })
+                        .unwrap_or_default()
+                } else {
+                    match dotenvy::from_path_iter(&path) {
+                        Ok(iter) => iter
+                            .filter_map(|item| {
+                                item.map_err(|err| warn!("env_file {}: {err}", display_path(&path)))
+                                    .ok()
+                            })
+                            .collect(),
+                        Err(err) => {
+                            trace!("env_file: {err}");
+                            vec![]
                         }
                     }
-                    Err(err) => trace!("env_file: {err}"),
+                };
+                for (k, v) in items {
+                    env.insert(k, (v, path.clone()));
                 }
             }
             Ok(env)
         })
     }
+    pub fn env_json(&self) -> eyre::Result<EnvJson> {
+        let env = self
+            .env_with_sources()?
+            .iter()
+            .map(|(k, (value, source))| {
+                let kind = if self.config_files.contains_key(source) {
+                    EnvJsonKind::Config
+                } else {
+                    EnvJsonKind::EnvFile
+                };
+                // relative to the directory the config was loaded from, see `load_from_dir`
+                let source = match &self.dir {
+                    Some(dir) => dir.join(source),
+                    None => absolute_path(source),
+                };
+                let entry = EnvJsonEntry {
+                    value: value.clone(),
+                    source,
+                    kind,
+                };
+                (k.clone(), entry)
+            })
+            .collect();
+        Ok(env)
+    }
+    /// env vars removed by an unset directive and the config file that removed them
+    pub fn env_remove(&self) -> eyre::Result<BTreeMap<String, PathBuf>> {
+        let env = self.env_with_sources()?;
+        Ok(self
+            .env_results()?
+            .env_remove
+            .iter()
+            .filter(|(k, _)| !env.contains_key(*k))
+            .map(|(k, source)| (k.clone(), source.clone()))
+            .collect())
+    }
+    /// the difference between `env()` and the pristine environment. PATH is not part of the
+    /// vars, instead `path_dirs()` and any PATH set in `[env]` are reported as directories
+    /// added in front of the pristine PATH like activation does
+    pub fn env_diff(&self) -> eyre::Result<ConfigEnvDiff> {
+        Ok(diff_env(
+            &env::PRISTINE_ENV,
+            &env::PATH,
+            self.env()?,
+            self.env_remove()?.into_keys().collect(),
+            self.path_dirs()?,
+        ))
+    }
     pub fn env_results(&self) -> eyre::Result<&EnvResults> {
         self.env.get_or_try_init(|| self.load_env())
     }
     pub fn path_dirs(&self) -> eyre::Result<&Vec<PathBuf>> {
         Ok(&self.env_results()?.env_paths)
     }
+    /// the built-in and settings shorthands overridden by the `[shorthands]` tables in this
+    /// config's files, these are cached per config so they never apply to other projects
     pub fn get_shorthands(&self) -> &Shorthands {
-        self.shorthands
-            .get_or_init(|| get_shorthands(&Settings::get()))
+        self.shorthands.get_or_init(|| {
+            let mut shorthands = get_shorthands(&Settings::get());
+            // lowest precedence first so higher precedence config files override them
+            for cf in self.config_files.values().rev() {
+                shorthands.extend(cf.shorthands());
+            }
+            shorthands
+        })
     }
 
     pub fn get_repo_url(&self, plugin_name: &String) -> Option<String> {
@@ -163,20 +309,46 @@ impl Config {
             .get_or_init(|| self.load_all_tasks())
     }
 
+    /// the highest-precedence config file that sets a version of `fa`
+    #[allow(dead_code)]
+    pub fn source_of_tool(&self, fa: &ForgeArg) -> Option<PathBuf> {
+        self.config_files
+            .iter()
+            .find(|(_, cf)| cf.to_toolset().versions.contains_key(fa))
+            .map(|(p, _)| absolute_path(p))
+    }
+
+    /// the config file that defines the task `name` (or an alias of it), or the task's own
+    /// file if it was loaded from a tasks directory
+    pub fn source_of_task(&self, name: &str) -> Option<PathBuf> {
+        self.tasks_with_aliases()
+            .get(name)
+            .map(|t| absolute_path(&t.config_source))
+    }
+
     pub fn is_activated(&self) -> bool {
         env::var("__MISE_DIFF").is_ok()
     }
 
+    /// follows the alias chain until it reaches a concrete version
+    /// config file aliases take precedence over the ones provided by the forge, once a config
+    /// file alias matched the chain only follows those so the forge is never queried
     pub fn resolve_alias(&self, forge: &dyn Forge, v: &str) -> Result<String> {
-        if let Some(plugin_aliases) = self.aliases.get(forge.fa()) {
-            if let Some(alias) = plugin_aliases.get(v) {
-                return Ok(alias.clone());
+        let config_aliases = self.aliases.get(forge.fa());
+        let forge_aliases = OnceCell::new();
+        let config_only = Cell::new(false);
+        follow_aliases(forge.fa(), v, |v| {
+            if let Some(alias) = config_aliases.and_then(|aliases| aliases.get(v)) {
+                config_only.set(true);
+                return Ok(Some(alias));
             }
-        }
-        if let Some(alias) = forge.get_aliases()?.get(v) {
-            return Ok(alias.clone());
-        }
-        Ok(v.to_string())
+            if config_only.get() {
+                return Ok(None);
+            }
+            Ok(forge_aliases
+                .get_or_try_init(|| forge.get_aliases())?
+                .get(v))
+        })
     }
 
     fn load_all_aliases(&self) -> AliasMap {
@@ -210,7 +382,8 @@ impl Config {
     }
 
     pub fn load_all_tasks(&self) -> HashMap<String, Task> {
-        self.config_files
+        let all = self
+            .config_files
             .values()
             .collect_vec()
             .into_par_iter()
@@ -237,6 +410,8 @@ impl Config {
             .collect_vec()
             .into_par_iter()
             .flat_map(|either| match either {
+                // remote tasks aren't fetched here since the config is loaded for every shim
+                // and prompt, see `Task::fetch_remote`
                 Either::Left(cf) => cf.tasks().into_iter().cloned().collect(),
                 Either::Right(path) => match Task::from_path(&path) {
                     Ok(task) => vec![task],
@@ -246,17 +421,43 @@ impl Config {
                     }
                 },
             })
-            .flat_map(|t| {
-                t.aliases
-                    .iter()
-                    .map(|a| (a.to_string(), t.clone()))
-                    .chain(once((t.name.clone(), t.clone())))
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>()
-            .into_iter()
-            .rev()
-            .collect()
+            .collect::<Vec<_>>();
+        // earlier tasks take precedence, except that explicitly named tasks win over ones
+        // named after their path in a tasks directory
+        let mut tasks: IndexMap<String, Task> = IndexMap::new();
+        for t in all {
+            match tasks.entry(t.name.clone()) {
+                Entry::Vacant(e) => {
+                    e.insert(t);
+                }
+                Entry::Occupied(mut e) if e.get().derived_name != t.derived_name => {
+                    let (explicit, derived) = match t.derived_name {
+                        true => (e.get(), &t),
+                        false => (&t, e.get()),
+                    };
+                    warn!(
+                        "task {} in {} is shadowed by the explicitly named task in {}",
+                        t.name,
+                        display_path(&derived.config_source),
+                        display_path(&explicit.config_source),
+                    );
+                    if !t.derived_name {
+                        e.insert(t);
+                    }
+                }
+                Entry::Occupied(_) => {}
+            }
+        }
+        // aliases never shadow a task name, if several tasks have the same alias it goes to the
+        // one that comes first, like with task names
+        let aliases = tasks
+            .values()
+            .flat_map(|t| t.aliases.iter().map(|a| (a.to_string(), t.clone())))
+            .collect_vec();
+        for (alias, t) in aliases {
+            tasks.entry(alias).or_insert(t);
+        }
+        tasks.into_iter().collect()
     }
 
     pub fn get_tracked_config_files(&self) -> Result<ConfigMap> {
@@ -296,36 +497,263 @@ impl Config {
     }
 
     fn validate(&self) -> Result<()> {
-        for cf in self.config_files.values() {
-            if let Some(min) = cf.min_version() {
-                let cur = &*version::V;
-                ensure!(
-                    cur >= min,
-                    "mise version {} is required, but you are using {}",
-                    style::eyellow(min),
-                    style::eyellow(cur)
-                );
-            }
+        if let Some(err) = self.diagnose_errors().into_iter().next() {
+            bail!("{}: {}", display_path(&err.path), err.message);
         }
         Ok(())
     }
 
+    /// all problems found in the config files, unlike `validate` this includes warnings
+    /// and does not stop at the first error
+    pub fn diagnose(&self) -> Vec<Diagnostic> {
+        self.diagnose_errors()
+            .into_iter()
+            .chain(self.diagnose_warnings())
+            .collect()
+    }
+
+    fn diagnose_errors(&self) -> Vec<Diagnostic> {
+        let cur = &*version::V;
+        self.config_files
+            .values()
+            .filter_map(|cf| match cf.min_version() {
+                Some(min) if cur < min => Some(Diagnostic::error(
+                    cf.get_path(),
+                    format!(
+                        "mise version {} is required, but you are using {}",
+                        style::eyellow(min),
+                        style::eyellow(cur)
+                    ),
+                )),
+                _ => None,
+            })
+            .chain(self.diagnose_strict_errors())
+            .filter(|d| d.severity == Severity::Error)
+            .collect()
+    }
+
+    /// unknown keys are only errors with strict_config, otherwise they are warnings that
+    /// `mise doctor` reports so they aren't repeated on every load, e.g.: by hook-env
+    fn diagnose_strict_errors(&self) -> Vec<Diagnostic> {
+        if !Settings::get().strict_config {
+            return vec![];
+        }
+        let mut diagnostics = self.diagnose_unknown_keys();
+        diagnostics.extend(self.diagnose_unknown_settings());
+        diagnostics
+    }
+
+    /// keys that aren't part of the config file schema, errors with the strict_config setting
+    fn diagnose_unknown_keys(&self) -> Vec<Diagnostic> {
+        let severity = unknown_key_severity();
+        self.config_files
+            .iter()
+            .flat_map(|(path, cf)| {
+                cf.unknown_keys().into_iter().map(|k| Diagnostic {
+                    severity,
+                    path: path.clone(),
+                    message: format!("unknown key: {k}"),
+                })
+            })
+            .collect()
+    }
+
+    /// unknown keys in `[settings]`, `validate` doesn't warn about these since loading the
+    /// settings already reports them
+    fn diagnose_unknown_settings(&self) -> Vec<Diagnostic> {
+        let severity = unknown_key_severity();
+        self.config_files
+            .iter()
+            .filter(|(_, cf)| cf.get_type() == ConfigFileType::MiseToml)
+            .flat_map(|(path, _)| diagnose_settings_keys(path))
+            .map(|d| Diagnostic { severity, ..d })
+            .collect()
+    }
+
+    fn diagnose_warnings(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        diagnostics.extend(
+            self.diagnose_unknown_keys()
+                .into_iter()
+                .chain(self.diagnose_unknown_settings())
+                .filter(|d| d.severity == Severity::Warning),
+        );
+        for (path, cf) in &self.config_files {
+            let config_root = path.parent().unwrap_or(path);
+            for task in cf.tasks() {
+                if let Some(file) = &task.file {
+                    if !config_root.join(file).exists() {
+                        diagnostics.push(Diagnostic::warning(
+                            path,
+                            format!("task {} file not found: {}", task.name, display_path(file)),
+                        ));
+                    }
+                }
+            }
+            for directive in cf.env_entries() {
+                if let EnvDirective::File(file) = directive {
+                    let file = file.to_string_lossy();
+                    if file.contains("{{") || file.contains("{%") {
+                        continue;
+                    }
+                    let file = normalize_path(config_root, &file);
+                    if !file.exists() {
+                        diagnostics.push(Diagnostic::warning(
+                            path,
+                            format!("env_file not found: {}", display_path(&file)),
+                        ));
+                    }
+                }
+            }
+        }
+        diagnostics
+    }
+
+    /// errors if tasks depend on each other in a cycle, this loads every task so unlike
+    /// `validate` it is only run by commands that use tasks
+    pub fn validate_tasks(&self) -> Result<()> {
+        check_cycles(self.tasks_with_aliases())
+    }
+
+    /// tool versions requested by config files that don't match any installed or remote
+    /// version, e.g.: a typo like `node = "20.x.99"`. This builds the toolset and may fetch
+    /// remote versions so unlike `validate` it isn't run when loading the config.
+    pub fn validate_tools(&self) -> Result<Vec<Diagnostic>> {
+        let ts = ToolsetBuilder::new().build(self)?;
+        let aliases = self.get_all_aliases();
+        let requests = ts
+            .versions
+            .values()
+            .filter_map(|tvl| match &tvl.source {
+                ToolSource::MiseToml(path)
+                | ToolSource::ToolVersions(path)
+                | ToolSource::LegacyVersionFile(path) => Some((path, tvl)),
+                _ => None,
+            })
+            .flat_map(|(path, tvl)| tvl.requests.iter().map(move |(tvr, _)| (path, tvr)))
+            .collect_vec();
+        let diagnostics = requests
+            .into_par_iter()
+            .filter_map(|(path, tvr)| {
+                let fa = tvr.forge();
+                let forge = forge::get(fa);
+                if !forge.is_installed() {
+                    let message = format!("{fa} is not installed, can't check {tvr}");
+                    return Some(Diagnostic::error(path, message));
+                }
+                let requested = tvr.version();
+                let resolved = match tvr {
+                    ToolVersionRequest::Version(_, v) => {
+                        let aliases = aliases.get(fa);
+                        follow_aliases(fa, v, |v| Ok(aliases.and_then(|a| a.get(v))))
+                    }
+                    _ => Ok(requested.clone()),
+                };
+                let exists = resolved.and_then(|v| Ok((tool_version_exists(&*forge, &v)?, v)));
+                let message = match exists {
+                    Ok((true, _)) => return None,
+                    Ok((false, v)) if v != requested => {
+                        format!("no version of {fa} matches {requested} ({v})")
+                    }
+                    Ok((false, _)) => format!("no version of {fa} matches {requested}"),
+                    Err(err) => format!("failed to resolve {tvr}: {err:#}"),
+                };
+                Some(Diagnostic::error(path, message))
+            })
+            .collect();
+        Ok(diagnostics)
+    }
+
     fn load_env(&self) -> Result<EnvResults> {
+        let env = self.resolve_env()?;
+        for (source, err) in &env.errors {
+            warn!("{}: {err}", display_path(source));
+        }
+        Ok(env)
+    }
+
+    fn resolve_env(&self) -> Result<EnvResults> {
         let entries = self
             .config_files
             .iter()
             .rev()
             .flat_map(|(source, cf)| cf.env_entries().into_iter().map(|e| (e, source.clone())))
-            .collect();
-        EnvResults::resolve(&env::PRISTINE_ENV, entries)
+            .collect_vec();
+        let project_root = self.project_root.as_deref();
+        // decrypted values must never be written to disk so encrypted env files aren't cached
+        if *env::MISE_NO_ENV_CACHE || may_load_encrypted_env_file(&entries) {
+            return EnvResults::resolve(&env::PRISTINE_ENV, entries, project_root);
+        }
+        let cache_dir = dirs::CACHE.join("env");
+        let cache_file = cache_dir.join(format!("{}.msgpack.z", self.env_cache_key()));
+        if !cache_file.exists() {
+            prune_env_cache(&cache_dir);
+        }
+        let cache = CacheManager::new(cache_file.clone());
+        let env = cache.get_or_try_init(|| {
+            EnvResults::resolve(&env::PRISTINE_ENV, entries.clone(), project_root)
+        })?;
+        // dotenv files and sourced scripts are only known after resolving so they can't be part
+        // of the key, instead the cached results are discarded if any of them changed since
+        let cache_age = file::modified_duration(&cache_file).unwrap_or_default();
+        let stale = env.env_files.iter().chain(env.env_scripts.iter()).any(|f| {
+            // creating or deleting a file changes the mtime of its directory
+            let f = match f.exists() {
+                true => f.as_path(),
+                false => f.parent().unwrap_or(f),
+            };
+            file::modified_duration(f).map_or(true, |d| d < cache_age)
+        });
+        if !stale {
+            return Ok(env.clone());
+        }
+        trace!("env cache is stale: {}", display_path(&cache_file));
+        let env = EnvResults::resolve(&env::PRISTINE_ENV, entries, project_root)?;
+        if let Err(err) = cache.write(&env) {
+            warn!(
+                "failed to write cache file: {} {:#}",
+                display_path(&cache_file),
+                err
+            );
+        }
+        Ok(env)
     }
 
-    pub fn watch_files(&self) -> eyre::Result<BTreeSet<&Path>> {
+    /// the env cache is invalidated when any config file, its mtime or trust status changes,
+    /// or when the settings, pristine env or current directory are different
+    /// shell bookkeeping vars are left out so every shell doesn't get its own cache file
+    fn env_cache_key(&self) -> String {
+        let config_files = self
+            .config_files
+            .keys()
+            .map(|p| {
+                let mtime = p.metadata().and_then(|m| m.modified()).ok();
+                (p, mtime, config_file::is_trusted(p))
+            })
+            .collect_vec();
+        let settings = serde_json::to_string(&*Settings::get()).unwrap_or_default();
+        let pristine_env = env::PRISTINE_ENV
+            .iter()
+            .filter(|(k, _)| !["OLDPWD", "SHLVL", "_"].contains(&k.as_str()))
+            .collect::<BTreeMap<_, _>>();
+        hash_to_str(&(config_files, settings, pristine_env, &self.dir))
+    }
+
+    /// the config files that would be loaded for this config's directory now, unlike
+    /// `config_files` this includes files created since it was loaded
+    pub fn find_config_paths(&self) -> Vec<PathBuf> {
+        let settings = Settings::get();
+        let legacy_files = load_legacy_files(&settings);
+        find_config_paths(self.dir.as_deref(), &settings, &legacy_files)
+    }
+
+    pub fn watch_files(&self) -> eyre::Result<BTreeSet<PathBuf>> {
         Ok(self
             .config_files
             .keys()
             .chain(self.env_results()?.env_files.iter())
-            .map(|p| p.as_path())
+            .cloned()
+            .chain(self.tasks().values().filter_map(|t| t.remote_cache_path()))
             .collect())
     }
 
@@ -336,6 +764,150 @@ impl Config {
     }
 }
 
+/// true if any env_file in `entries` is encrypted, templated paths are only known once
+/// resolved so they are assumed to be
+fn may_load_encrypted_env_file(entries: &[(EnvDirective, PathBuf)]) -> bool {
+    entries.iter().any(|(directive, source)| match directive {
+        EnvDirective::File(file) => {
+            let file = file.to_string_lossy();
+            let config_root = source.parent().unwrap_or(source);
+            file.contains("{{")
+                || file.contains("{%")
+                || sops::is_encrypted(&normalize_path(config_root, &file))
+        }
+        _ => false,
+    })
+}
+
+/// removes env cache files that haven't been written in a day, these are left behind whenever
+/// the config or environment changes
+fn prune_env_cache(cache_dir: &Path) {
+    let Ok(files) = file::ls(cache_dir) else {
+        return;
+    };
+    for f in files {
+        if file::modified_duration(&f).is_ok_and(|d| d > duration::DAILY) {
+            if let Err(err) = file::remove_file(&f) {
+                debug!("failed to remove {}: {err:#}", display_path(&f));
+            }
+        }
+    }
+}
+
+/// warns about keys in the `[settings]` table that aren't mise settings, e.g.: typos
+fn diagnose_settings_keys(path: &Path) -> Vec<Diagnostic> {
+    let Ok(toml) = file::read_to_string(path).map(|raw| raw.parse::<toml::Table>()) else {
+        return vec![];
+    };
+    let Some(settings) = toml.ok().and_then(|t| t.get("settings").cloned()) else {
+        return vec![];
+    };
+    let known = <Settings as confique::Config>::META
+        .fields
+        .iter()
+        .map(|f| f.name)
+        .collect::<HashSet<_>>();
+    settings
+        .as_table()
+        .into_iter()
+        .flat_map(|t| t.keys())
+        .filter(|k| !known.contains(k.as_str()))
+        .map(|k| Diagnostic::warning(path, format!("unknown setting: {k}")))
+        .collect()
+}
+
+fn diff_env(
+    pristine: &HashMap<String, String>,
+    pristine_path: &[PathBuf],
+    mut env: IndexMap<String, String>,
+    remove: Vec<String>,
+    path_dirs: &[PathBuf],
+) -> ConfigEnvDiff {
+    let mut diff = ConfigEnvDiff::default();
+    let env_path = env.shift_remove("PATH");
+    for (k, v) in env {
+        match pristine.get(&k) {
+            Some(old) if *old == v => {}
+            Some(old) => {
+                diff.changed.insert(k, (old.clone(), v));
+            }
+            None => {
+                diff.added.insert(k, v);
+            }
+        }
+    }
+    let unset_path = remove.iter().any(|k| k == "PATH");
+    for k in remove.into_iter().filter(|k| k != "PATH") {
+        if let Some(old) = pristine.get(&k) {
+            diff.removed.insert(k, old.clone());
+        }
+    }
+    // the pristine PATH is only dropped if PATH itself is unset
+    let kept: &[PathBuf] = if unset_path { &[] } else { pristine_path };
+    diff.path_added = path_dirs
+        .iter()
+        .cloned()
+        .chain(env_path.iter().flat_map(split_paths))
+        .filter(|p| !kept.contains(p))
+        .unique()
+        .collect();
+    if unset_path {
+        diff.path_removed = pristine_path
+            .iter()
+            .filter(|p| !diff.path_added.contains(p))
+            .cloned()
+            .unique()
+            .collect();
+    }
+    diff
+}
+
+fn unknown_key_severity() -> Severity {
+    match Settings::get().strict_config {
+        true => Severity::Error,
+        false => Severity::Warning,
+    }
+}
+
+/// follows the alias chain from `v` until it reaches a version that isn't an alias
+fn follow_aliases<'a>(
+    fa: &ForgeArg,
+    v: &str,
+    get_alias: impl Fn(&str) -> Result<Option<&'a String>>,
+) -> Result<String> {
+    let mut seen = vec![v.to_string()];
+    let mut v = v.to_string();
+    loop {
+        match get_alias(&v)? {
+            Some(alias) if *alias != v => {
+                if seen.contains(alias) {
+                    let chain = seen.iter().chain(once(alias)).join(" -> ");
+                    bail!("circular alias detected for {fa}: {chain}");
+                }
+                seen.push(alias.clone());
+                v = alias.clone();
+            }
+            _ => return Ok(v),
+        }
+    }
+}
+
+/// true if an installed or remote version matches `v`, a version with aliases already resolved
+fn tool_version_exists(forge: &dyn Forge, v: &str) -> Result<bool> {
+    let matches = |query: &str| -> Result<bool> {
+        Ok(!forge.list_installed_versions_matching(query)?.is_empty()
+            || !forge.list_versions_matching(query)?.is_empty())
+    };
+    match v.split_once(':') {
+        Some(("ref", _)) => Ok(true),
+        Some(("path", p)) => Ok(Path::new(p).exists()),
+        Some(("prefix", p)) => matches(p),
+        Some((part, v)) if part.starts_with("sub-") => matches(v),
+        _ if v == "system" => Ok(true),
+        _ => matches(v),
+    }
+}
+
 fn get_project_root(config_files: &ConfigMap) -> Option<PathBuf> {
     config_files
         .values()
@@ -343,42 +915,116 @@ fn get_project_root(config_files: &ConfigMap) -> Option<PathBuf> {
         .map(|pr| pr.to_path_buf())
 }
 
+fn absolute_path(p: &Path) -> PathBuf {
+    p.absolutize()
+        .map_or_else(|_| p.to_path_buf(), |p| p.to_path_buf())
+}
+
+/// finds an env_file by searching up from the config directory
+fn find_env_file(dir: Option<&Path>, env_file: &Path) -> Option<PathBuf> {
+    file::find_up(dir?, &[&env_file.to_string_lossy()]).filter(|p| p.is_file())
+}
+
 fn load_legacy_files(settings: &Settings) -> BTreeMap<String, Vec<String>> {
     if !settings.legacy_version_file {
         return BTreeMap::new();
     }
-    let legacy = forge::list()
-        .into_par_iter()
+    // plugins may need to run a script to list their legacy filenames so the result is cached
+    // until the installed plugins change, results with errors are not cached
+    let tools = legacy_tools(settings);
+    let key = legacy_files_cache_key(settings, &tools);
+    let cache_file = dirs::CACHE
+        .join("legacy_filenames")
+        .join(format!("{key}.msgpack.z"));
+    let mut uncached = None;
+    let legacy = match CacheManager::new(cache_file).get_or_try_init(|| {
+        let (legacy, errors): (Vec<_>, Vec<_>) = tools
+            .par_iter()
+            .map(|tool| {
+                tool.legacy_filenames().map(|filenames| {
+                    let manifests = if settings.legacy_version_manifests {
+                        tool.legacy_manifests()
+                    } else {
+                        vec![]
+                    };
+                    let manifests = manifests.into_iter().map(|m| m.filename());
+                    filenames
+                        .iter()
+                        .map(|f| f.as_str())
+                        .chain(manifests)
+                        .map(|f| (f.to_string(), tool.id().to_string()))
+                        .collect_vec()
+                })
+            })
+            .partition_map(|r| match r {
+                Ok(filenames) => Either::Left(filenames),
+                Err(err) => Either::Right(err),
+            });
+        let legacy = legacy
+            .into_iter()
+            .flatten()
+            .collect::<Vec<(String, String)>>();
+        if errors.is_empty() {
+            return Ok(legacy);
+        }
+        for err in errors {
+            eprintln!("Error: {err}");
+        }
+        uncached = Some(legacy);
+        Err(eyre!("failed to list legacy filenames"))
+    }) {
+        Ok(legacy) => legacy.clone(),
+        Err(_) => uncached.unwrap_or_default(),
+    };
+
+    let custom = settings
+        .legacy_version_filenames
+        .iter()
+        .filter(|(_, tool)| !settings.legacy_version_file_disable_tools.contains(*tool))
+        .map(|(filename, tool)| (filename.clone(), tool.clone()));
+
+    let mut legacy_filenames = BTreeMap::new();
+    for (filename, plugin) in legacy.into_iter().chain(custom) {
+        let plugins: &mut Vec<String> = legacy_filenames.entry(filename).or_default();
+        if !plugins.contains(&plugin) {
+            plugins.push(plugin);
+        }
+    }
+    legacy_filenames
+}
+
+/// the tools that can have legacy version files and aren't disabled, others are skipped so
+/// their plugins don't run a script and their plugin dirs aren't part of the cache key
+fn legacy_tools(settings: &Settings) -> Vec<AForge> {
+    forge::list()
+        .into_iter()
+        .filter(|tool| tool.has_legacy_filenames() || !tool.legacy_manifests().is_empty())
         .filter(|tool| {
             !settings
                 .legacy_version_file_disable_tools
                 .contains(tool.id())
         })
-        .filter_map(|tool| match tool.legacy_filenames() {
-            Ok(filenames) => Some(
-                filenames
-                    .iter()
-                    .map(|f| (f.to_string(), tool.id().to_string()))
-                    .collect_vec(),
-            ),
-            Err(err) => {
-                eprintln!("Error: {err}");
-                None
-            }
-        })
-        .collect::<Vec<Vec<(String, String)>>>()
-        .into_iter()
-        .flatten()
-        .collect::<Vec<(String, String)>>();
+        .collect()
+}
 
-    let mut legacy_filenames = BTreeMap::new();
-    for (filename, plugin) in legacy {
-        legacy_filenames
-            .entry(filename)
-            .or_insert_with(Vec::new)
-            .push(plugin);
-    }
-    legacy_filenames
+/// changes when mise is upgraded, a plugin is installed, updated or removed, or the disabled
+/// tools change
+fn legacy_files_cache_key(settings: &Settings, tools: &[AForge]) -> String {
+    let mtime = |p: PathBuf| p.metadata().and_then(|m| m.modified()).ok();
+    let forges = tools
+        .iter()
+        .map(|f| {
+            let plugin_path = dirs::PLUGINS.join(f.id());
+            let script = mtime(plugin_path.join("bin/list-legacy-filenames"));
+            (f.id().to_string(), mtime(plugin_path), script)
+        })
+        .sorted()
+        .collect_vec();
+    hash_to_str(&(
+        &*version::VERSION,
+        settings.legacy_version_manifests,
+        forges,
+    ))
 }
 
 pub static DEFAULT_CONFIG_FILENAMES: Lazy<Vec<String>> = Lazy::new(|| {
@@ -396,10 +1042,11 @@ pub static DEFAULT_CONFIG_FILENAMES: Lazy<Vec<String>> = Lazy::new(|| {
             ".rtx.local.toml".into(),
             ".mise.local.toml".into(),
         ];
-        if let Some(env) = &*env::MISE_ENV {
+        // later profiles take precedence over earlier ones
+        for env in &*env::MISE_ENV {
             filenames.push(format!(".config/mise/config.{env}.toml"));
             filenames.push(format!(".config/mise.{env}.toml"));
-            filenames.push(format!(".mise/config.{env}.local.toml"));
+            filenames.push(format!(".mise/config.{env}.toml"));
             filenames.push(format!(".mise.{env}.toml"));
             filenames.push(format!(".config/mise/config.{env}.local.toml"));
             filenames.push(format!(".config/mise.{env}.local.toml"));
@@ -415,19 +1062,51 @@ pub static DEFAULT_CONFIG_FILENAMES: Lazy<Vec<String>> = Lazy::new(|| {
     }
 });
 
-pub fn load_config_paths(config_filenames: &[String]) -> Vec<PathBuf> {
-    let mut config_files = Vec::new();
-
+pub fn load_config_paths(config_filenames: &[String], override_filename: &str) -> Vec<PathBuf> {
     // The current directory is not always available, e.g.
     // when a directory was deleted or inside FUSE mounts.
     match env::current_dir() {
         Ok(current_dir) => {
-            config_files.extend(file::FindUp::new(&current_dir, config_filenames));
+            load_config_paths_from(Some(&current_dir), config_filenames, override_filename)
         }
         Err(error) => {
             debug!("error getting current dir: {error}");
+            load_config_paths_from(None, config_filenames, override_filename)
         }
-    };
+    }
+}
+
+fn find_config_paths(
+    dir: Option<&Path>,
+    settings: &Settings,
+    legacy_files: &BTreeMap<String, Vec<String>>,
+) -> Vec<PathBuf> {
+    let config_filenames = legacy_files
+        .keys()
+        .chain(DEFAULT_CONFIG_FILENAMES.iter())
+        .cloned()
+        .collect_vec();
+    load_config_paths_from(dir, &config_filenames, &settings.override_config_filename)
+}
+
+fn load_config_paths_from(
+    dir: Option<&Path>,
+    config_filenames: &[String],
+    override_filename: &str,
+) -> Vec<PathBuf> {
+    let mut config_files = Vec::new();
+    if let Some(dir) = dir {
+        let filenames = config_filenames
+            .iter()
+            .cloned()
+            .chain(once(override_filename.to_string()))
+            .collect_vec();
+        // override files take precedence over everything else, including global config
+        let (overrides, project): (Vec<_>, Vec<_>) = file::FindUp::new(dir, &filenames)
+            .partition(|p| is_override_config(p, override_filename));
+        config_files.extend(overrides);
+        config_files.extend(project);
+    }
 
     config_files.extend(global_config_files());
     config_files.extend(system_config_files());
@@ -435,6 +1114,12 @@ pub fn load_config_paths(config_filenames: &[String]) -> Vec<PathBuf> {
     config_files.into_iter().unique().collect()
 }
 
+/// true if `path` is a machine-specific override file, these are never a project's shared
+/// config
+pub fn is_override_config(path: &Path, override_filename: &str) -> bool {
+    !override_filename.is_empty() && path.file_name() == Some(override_filename.as_ref())
+}
+
 pub fn is_global_config(path: &Path) -> bool {
     global_config_files()
         .iter()
@@ -474,29 +1159,120 @@ pub fn system_config_files() -> Vec<PathBuf> {
 fn load_all_config_files(
     config_filenames: &[PathBuf],
     legacy_filenames: &BTreeMap<String, Vec<String>>,
+    override_filename: &str,
+    skip_invalid: bool,
 ) -> Result<ConfigMap> {
-    Ok(config_filenames
+    let parsed = config_filenames
         .iter()
         .unique()
         .collect_vec()
         .into_par_iter()
         .map(|f| {
-            let cf = parse_config_file(f, legacy_filenames).wrap_err_with(|| {
-                format!(
-                    "error parsing config file: {}",
-                    style::ebold(display_path(f))
-                )
-            })?;
-            if let Err(err) = Tracker::track(f) {
-                warn!("tracking config: {err:#}");
-            }
-            Ok((f.clone(), cf))
+            let cf = load_config_file(f, legacy_filenames).map(|mut cf| {
+                if is_override_config(f, override_filename) {
+                    cf.set_project_root(None);
+                }
+                cf
+            });
+            (f.clone(), cf)
         })
-        .collect::<Vec<Result<_>>>()
-        .into_iter()
-        .collect::<Result<Vec<_>>>()?
+        .collect::<Vec<_>>()
         .into_iter()
-        .collect())
+        .map(|(f, cf)| Ok(skip_if_invalid(&f, cf, skip_invalid)?.map(|cf| (f, cf))))
+        .flatten_ok()
+        .collect::<Result<Vec<_>>>()?;
+    let mut config_files = ConfigMap::new();
+    for (f, cf) in parsed {
+        if !config_files.contains_key(&f) {
+            let stack = &mut vec![];
+            add_config_file(
+                &mut config_files,
+                f,
+                cf,
+                legacy_filenames,
+                skip_invalid,
+                stack,
+            )?;
+        }
+    }
+    Ok(config_files)
+}
+
+/// adds a config file followed by the files it includes so the including file takes precedence
+/// later entries in `include` take precedence over earlier ones
+fn add_config_file(
+    config_files: &mut ConfigMap,
+    f: PathBuf,
+    cf: Box<dyn ConfigFile>,
+    legacy_filenames: &BTreeMap<String, Vec<String>>,
+    skip_invalid: bool,
+    stack: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let includes = cf.includes();
+    let project_root = cf.project_root().map(Path::to_path_buf);
+    config_files.insert(f.clone(), cf);
+    stack.push(f);
+    for include in includes.into_iter().rev() {
+        if stack.contains(&include) {
+            let path = stack.iter().chain(once(&include)).map(|p| display_path(p));
+            bail!(
+                "circular include detected: {}",
+                path.collect_vec().join(" -> ")
+            );
+        }
+        if config_files.contains_key(&include) {
+            continue;
+        }
+        let cf = load_config_file(&include, legacy_filenames);
+        let Some(mut cf) = skip_if_invalid(&include, cf, skip_invalid)? else {
+            continue;
+        };
+        cf.set_project_root(project_root.clone());
+        add_config_file(
+            config_files,
+            include,
+            cf,
+            legacy_filenames,
+            skip_invalid,
+            stack,
+        )?;
+    }
+    stack.pop();
+    Ok(())
+}
+
+/// with skip_invalid (the skip_invalid_config_files setting) a file that fails to parse is
+/// logged and skipped so the others can still be used, unless it is the file explicitly set
+/// with MISE_CONFIG_FILE
+fn skip_if_invalid(
+    f: &Path,
+    cf: Result<Box<dyn ConfigFile>>,
+    skip_invalid: bool,
+) -> Result<Option<Box<dyn ConfigFile>>> {
+    match cf {
+        Ok(cf) => Ok(Some(cf)),
+        Err(err) if skip_invalid && env::var_path("MISE_CONFIG_FILE").as_deref() != Some(f) => {
+            error!("Error loading config file: {:#}", err);
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+fn load_config_file(
+    f: &PathBuf,
+    legacy_filenames: &BTreeMap<String, Vec<String>>,
+) -> Result<Box<dyn ConfigFile>> {
+    let cf = parse_config_file(f, legacy_filenames).wrap_err_with(|| {
+        format!(
+            "error parsing config file: {}",
+            style::ebold(display_path(f))
+        )
+    })?;
+    if let Err(err) = Tracker::track(f) {
+        warn!("tracking config: {err:#}");
+    }
+    Ok(cf)
 }
 
 fn parse_config_file(
@@ -515,14 +1291,14 @@ fn parse_config_file(
     }
 }
 
-fn load_aliases(config_files: &ConfigMap) -> AliasMap {
+fn load_aliases(settings: &Settings, config_files: &ConfigMap) -> AliasMap {
     let mut aliases: AliasMap = AliasMap::new();
 
-    for config_file in config_files.values() {
+    // lowest precedence first so higher precedence config files override them
+    for config_file in config_files.values().rev() {
         for (plugin, plugin_aliases) in config_file.aliases() {
-            for (from, to) in plugin_aliases {
-                aliases.entry(plugin.clone()).or_default().insert(from, to);
-            }
+            let base = aliases.entry(plugin).or_default();
+            merge_map(settings.merge_strategy, base, plugin_aliases);
         }
     }
 
@@ -570,4 +1346,551 @@ mod tests {
         let config = Config::load().unwrap();
         assert_debug_snapshot!(config);
     }
+
+    #[test]
+    fn test_load_from_dir() {
+        let config = Config::load_from_dir(&dirs::HOME.join("cwd")).unwrap();
+        let cwd_config = Config::load().unwrap();
+        assert_eq!(
+            config.config_files.keys().collect_vec(),
+            cwd_config.config_files.keys().collect_vec()
+        );
+
+        let config = Config::load_from_dir(&dirs::HOME).unwrap();
+        let tool_versions = dirs::HOME.join("cwd").join(".test-tool-versions");
+        assert!(cwd_config.config_files.contains_key(&tool_versions));
+        assert!(!config.config_files.contains_key(&tool_versions));
+    }
+
+    #[test]
+    fn test_source_of() {
+        let config = Config::load().unwrap();
+        let tiny: ForgeArg = "tiny".parse().unwrap();
+        assert_eq!(
+            config.source_of_tool(&tiny),
+            Some(dirs::HOME.join("cwd/.test-tool-versions"))
+        );
+        assert_eq!(
+            config.source_of_tool(&"dummy".parse::<ForgeArg>().unwrap()),
+            Some(dirs::HOME.join(".test-tool-versions"))
+        );
+        assert_eq!(
+            config.source_of_tool(&"nope".parse::<ForgeArg>().unwrap()),
+            None
+        );
+        assert_eq!(
+            config.source_of_task("lint"),
+            Some(dirs::HOME.join("config/config.toml"))
+        );
+        assert_eq!(
+            config.source_of_task("filetask"),
+            Some(dirs::HOME.join("cwd/.mise/tasks/filetask"))
+        );
+        assert_eq!(config.source_of_task("nope"), None);
+    }
+
+    #[test]
+    fn test_env_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".test.mise.toml");
+        file::write(&path, "[env]\nFOO = \"bar\"\n_.file = \".env\"\n").unwrap();
+        file::write(dir.path().join(".env"), "FROM_FILE=1\n").unwrap();
+        let config = Config::load_from_dir(dir.path()).unwrap();
+        let env = config.env_json().unwrap();
+        assert_eq!(env["FOO"].source, path);
+        assert_eq!(env["FOO"].kind, EnvJsonKind::Config);
+        assert_eq!(env["FROM_FILE"].source, dir.path().join(".env"));
+        assert_eq!(env["FROM_FILE"].kind, EnvJsonKind::EnvFile);
+        let json = serde_json::to_value(&env["FROM_FILE"]).unwrap();
+        assert_eq!(json["type"], "env_file");
+    }
+
+    #[test]
+    fn test_validate_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        file::write(
+            dir.path().join(".test.mise.toml"),
+            "[tasks.cycle-a]\ndepends = [\"cycle-b\"]\n[tasks.cycle-b]\ndepends = [\"cycle-a\"]\n",
+        )
+        .unwrap();
+        // loading doesn't check the tasks so commands that don't use them still work
+        let config = Config::load_from_dir(dir.path()).unwrap();
+        let err = config.validate_tasks().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "circular dependency detected in tasks: cycle-a -> cycle-b -> cycle-a"
+        );
+    }
+
+    #[test]
+    fn test_task_alias_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        let parent_path = dir.path().join(".test.mise.toml");
+        let sub_path = sub.join(".test.mise.toml");
+        file::create_dir_all(&sub).unwrap();
+        file::write(
+            &parent_path,
+            "[tasks.zeta]\nalias = \"x\"\n[tasks.same]\nrun = \"echo parent\"\n",
+        )
+        .unwrap();
+        file::write(
+            &sub_path,
+            "[tasks.alpha]\nalias = \"x\"\n[tasks.same]\nrun = \"echo sub\"\n",
+        )
+        .unwrap();
+        let load =
+            |path: &Path| -> Box<dyn ConfigFile> { Box::new(MiseToml::from_file(path).unwrap()) };
+        let config = Config {
+            config_files: ConfigMap::from([
+                (sub_path.clone(), load(&sub_path)),
+                (parent_path.clone(), load(&parent_path)),
+            ]),
+            ..Default::default()
+        };
+        // an alias defined in several files goes to the task from the file that also wins
+        // when tasks have the same name
+        let tasks = config.tasks_with_aliases();
+        assert_eq!(tasks["x"].config_source, tasks["same"].config_source);
+    }
+
+    #[test]
+    fn test_load_config_paths_override() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        for p in [
+            dir.path().join(".test.mise.toml"),
+            dir.path().join(".mise.override.toml"),
+            sub.join(".test.mise.toml"),
+        ] {
+            file::create_dir_all(p.parent().unwrap()).unwrap();
+            file::write(&p, "").unwrap();
+        }
+        let paths =
+            load_config_paths_from(Some(&sub), &DEFAULT_CONFIG_FILENAMES, ".mise.override.toml")
+                .into_iter()
+                .filter(|p| p.starts_with(dir.path()))
+                .collect_vec();
+        assert_eq!(
+            paths,
+            vec![
+                dir.path().join(".mise.override.toml"),
+                sub.join(".test.mise.toml"),
+                dir.path().join(".test.mise.toml"),
+            ]
+        );
+        let config_files =
+            load_all_config_files(&paths, &BTreeMap::new(), ".mise.override.toml", false).unwrap();
+        assert_eq!(config_files[&paths[0]].project_root(), None);
+        assert_eq!(get_project_root(&config_files), Some(sub));
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".test.mise.toml");
+        file::write(
+            &path,
+            formatdoc! {r#"
+                min_version = "9999.0.0"
+                env_file = ".env.missing"
+                [settings]
+                experimental = true
+                experimantal = true
+                [tasks.lint]
+                file = "scripts/lint.sh"
+                sorces = ["src/**"]
+                [task.build]
+                run = "cargo build"
+            "#},
+        )
+        .unwrap();
+        let cf: Box<dyn ConfigFile> = Box::new(MiseToml::from_file(&path).unwrap());
+        let config = Config {
+            config_files: ConfigMap::from([(path.clone(), cf)]),
+            ..Default::default()
+        };
+        let messages = config
+            .diagnose()
+            .into_iter()
+            .map(|d| {
+                assert_eq!(d.path, path);
+                (
+                    d.severity,
+                    console::strip_ansi_codes(&d.message).to_string(),
+                )
+            })
+            .collect_vec();
+        let version = &*version::V;
+        let dir = display_path(dir.path());
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    Severity::Error,
+                    format!("mise version 9999.0.0 is required, but you are using {version}")
+                ),
+                (Severity::Warning, "unknown key: tasks.lint.sorces".into()),
+                (Severity::Warning, "unknown key: task".into()),
+                (Severity::Warning, "unknown setting: experimantal".into()),
+                (
+                    Severity::Warning,
+                    "task lint file not found: scripts/lint.sh".into()
+                ),
+                (
+                    Severity::Warning,
+                    format!("env_file not found: {dir}/.env.missing")
+                ),
+            ]
+        );
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_load_all_config_files_include_project_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        let dotfiles = dir.path().join("dotfiles");
+        let path = project.join(".test.mise.toml");
+        let common = dotfiles.join("common.toml");
+        file::create_dir_all(&project).unwrap();
+        file::create_dir_all(&dotfiles).unwrap();
+        file::write(&path, "include = \"../dotfiles/common.toml\"\n").unwrap();
+        file::write(&common, "[tools]\ntiny = \"1\"\n").unwrap();
+
+        let config_files = load_all_config_files(&[path], &BTreeMap::new(), "", false).unwrap();
+        let cf = config_files
+            .iter()
+            .find(|(p, _)| p.ends_with("common.toml"))
+            .map(|(_, cf)| cf)
+            .unwrap();
+        assert_eq!(cf.project_root(), Some(project.as_path()));
+    }
+
+    #[test]
+    fn test_load_all_config_files_skip_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        let good = dir.path().join(".test.mise.toml");
+        let bad = sub.join(".test.mise.toml");
+        file::create_dir_all(&sub).unwrap();
+        file::write(&good, "[tools]\ntiny = \"1\"\n").unwrap();
+        file::write(&bad, "[tools\n").unwrap();
+        let paths = [bad.clone(), good.clone()];
+        let legacy = BTreeMap::new();
+
+        let err = load_all_config_files(&paths, &legacy, "", false).unwrap_err();
+        assert!(format!("{err:#}").contains(&bad.display().to_string()));
+
+        let config_files = load_all_config_files(&paths, &legacy, "", true).unwrap();
+        assert_eq!(config_files.keys().collect_vec(), vec![&good]);
+        assert_eq!(
+            get_project_root(&config_files),
+            Some(dir.path().to_path_buf())
+        );
+    }
+
+    #[test]
+    fn test_load_env_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".test.mise.toml");
+        file::write(
+            &path,
+            formatdoc! {r#"
+                [env]
+                FOO = "{{{{ nope }}}}"
+                BAR = "bar"
+                _.file = ".env.missing"
+            "#},
+        )
+        .unwrap();
+        let cf: Box<dyn ConfigFile> = Box::new(MiseToml::from_file(&path).unwrap());
+        let config = Config {
+            config_files: ConfigMap::from([(path.clone(), cf)]),
+            ..Default::default()
+        };
+        let env = config.load_env().unwrap();
+        assert_eq!(env.env["BAR"].0, "bar");
+        assert!(!env.env.contains_key("FOO"));
+        let errors = env
+            .errors
+            .iter()
+            .map(|(p, e)| {
+                assert_eq!(p, &path);
+                e.lines().next().unwrap().to_string()
+            })
+            .collect_vec();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("failed to parse template: '{{ nope }}'"));
+        assert!(errors[1].starts_with("failed to parse dotenv file:"));
+    }
+
+    #[test]
+    fn test_load_env_config_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        let parent_path = dir.path().join(".mise.toml");
+        let sub_path = sub.join(".mise.toml");
+        file::create_dir_all(&sub).unwrap();
+        file::write(
+            &parent_path,
+            formatdoc! {r#"
+                [env]
+                PARENT_DB = "{{{{ config_root }}}}/db.sqlite"
+            "#},
+        )
+        .unwrap();
+        file::write(
+            &sub_path,
+            formatdoc! {r#"
+                [env]
+                SUB_DB = "{{{{ config_root }}}}/db.sqlite"
+                PROJECT = "{{{{ project_root }}}}"
+            "#},
+        )
+        .unwrap();
+        let config = Config {
+            config_files: ConfigMap::from([
+                (
+                    sub_path.clone(),
+                    Box::new(MiseToml::from_file(&sub_path).unwrap()) as Box<dyn ConfigFile>,
+                ),
+                (
+                    parent_path.clone(),
+                    Box::new(MiseToml::from_file(&parent_path).unwrap()) as Box<dyn ConfigFile>,
+                ),
+            ]),
+            project_root: Some(sub.clone()),
+            ..Default::default()
+        };
+        let env = config.load_env().unwrap();
+        let db = |d: &Path| d.join("db.sqlite").to_string_lossy().to_string();
+        assert_eq!(env.env["PARENT_DB"].0, db(dir.path()));
+        assert_eq!(env.env["SUB_DB"].0, db(&sub));
+        assert_eq!(env.env["PROJECT"].0, sub.to_string_lossy());
+        assert!(env.errors.is_empty());
+    }
+
+    #[test]
+    fn test_validate_tools() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".test.mise.toml");
+        file::write(
+            &path,
+            formatdoc! {r#"
+                [tools]
+                tiny = ["3", "99.9", "old", "gone"]
+                not-installed = "1"
+
+                [alias.tiny]
+                old = "2.0"
+                gone = "9.9"
+            "#},
+        )
+        .unwrap();
+        let cf: Box<dyn ConfigFile> = Box::new(MiseToml::from_file(&path).unwrap());
+        let config_files = ConfigMap::from([(path.clone(), cf)]);
+        let config = Config {
+            aliases: load_aliases(&Settings::get(), &config_files),
+            config_files,
+            ..Default::default()
+        };
+        let diagnostics = config.validate_tools().unwrap();
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::error(&path, "no version of tiny matches 99.9"),
+                Diagnostic::error(&path, "no version of tiny matches gone (9.9)"),
+                Diagnostic::error(
+                    &path,
+                    "not-installed is not installed, can't check not-installed@1"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_shorthands_from_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        file::create_dir_all(&sub).unwrap();
+        let parent_path = dir.path().join(".test.mise.toml");
+        let sub_path = sub.join(".test.mise.toml");
+        file::write(
+            &parent_path,
+            formatdoc! {r#"
+                [shorthands]
+                elixir = "https://mirror/parent-elixir.git"
+                internal = "https://mirror/internal.git"
+            "#},
+        )
+        .unwrap();
+        file::write(
+            &sub_path,
+            formatdoc! {r#"
+                [shorthands]
+                elixir = "https://mirror/elixir.git"
+            "#},
+        )
+        .unwrap();
+        let load =
+            |path: &Path| -> Box<dyn ConfigFile> { Box::new(MiseToml::from_file(path).unwrap()) };
+        let config = Config {
+            config_files: ConfigMap::from([
+                (sub_path.clone(), load(&sub_path)),
+                (parent_path.clone(), load(&parent_path)),
+            ]),
+            ..Default::default()
+        };
+        let url = |config: &Config, name: &str| config.get_repo_url(&name.to_string());
+        assert_eq!(url(&config, "elixir").unwrap(), "https://mirror/elixir.git");
+        assert_eq!(
+            url(&config, "internal").unwrap(),
+            "https://mirror/internal.git"
+        );
+
+        let other = Config::default();
+        assert_ne!(url(&other, "elixir").unwrap(), "https://mirror/elixir.git");
+        assert_eq!(url(&other, "internal"), None);
+    }
+
+    #[test]
+    fn test_diff_env() {
+        let pristine = HashMap::from([
+            ("HOME".to_string(), "/home/me".to_string()),
+            ("EDITOR".to_string(), "vi".to_string()),
+            ("PAGER".to_string(), "less".to_string()),
+            ("PATH".to_string(), "/usr/bin:/bin".to_string()),
+        ]);
+        let pristine_path = vec![PathBuf::from("/usr/bin"), PathBuf::from("/bin")];
+        let env = IndexMap::from([
+            ("HOME".to_string(), "/home/me".to_string()),
+            ("EDITOR".to_string(), "nvim".to_string()),
+            ("NEW".to_string(), "1".to_string()),
+            ("PATH".to_string(), "/from/env:/usr/bin".to_string()),
+        ]);
+        let path_dirs = vec![PathBuf::from("/b"), PathBuf::from("/a")];
+        let diff = diff_env(
+            &pristine,
+            &pristine_path,
+            env.clone(),
+            vec!["PAGER".into(), "MISSING".into()],
+            &path_dirs,
+        );
+        assert_eq!(
+            diff,
+            ConfigEnvDiff {
+                added: IndexMap::from([("NEW".into(), "1".into())]),
+                changed: IndexMap::from([("EDITOR".into(), ("vi".into(), "nvim".into()))]),
+                removed: IndexMap::from([("PAGER".into(), "less".into())]),
+                path_added: vec!["/b".into(), "/a".into(), "/from/env".into()],
+                path_removed: vec![],
+            }
+        );
+
+        let diff = diff_env(
+            &pristine,
+            &pristine_path,
+            env,
+            vec!["PATH".into()],
+            &path_dirs,
+        );
+        assert_eq!(
+            diff.path_added,
+            vec![
+                PathBuf::from("/b"),
+                "/a".into(),
+                "/from/env".into(),
+                "/usr/bin".into()
+            ]
+        );
+        assert_eq!(diff.path_removed, vec![PathBuf::from("/bin")]);
+    }
+
+    #[test]
+    fn test_load_legacy_files_custom() {
+        let settings = Settings {
+            legacy_version_filenames: BTreeMap::from([
+                (".tinyrc".into(), "tiny".into()),
+                (".dummyrc".into(), "dummy".into()),
+            ]),
+            legacy_version_file_disable_tools: BTreeSet::from(["dummy".into()]),
+            ..(*Settings::get()).clone()
+        };
+        let legacy_files = load_legacy_files(&settings);
+        assert_eq!(legacy_files[".tinyrc"], vec!["tiny"]);
+        assert!(!legacy_files.contains_key(".dummyrc"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".tinyrc");
+        file::write(&path, "3.1.0\n").unwrap();
+        let cf = parse_config_file(&path, &legacy_files).unwrap();
+        assert_eq!(cf.get_type(), ConfigFileType::LegacyVersion);
+        assert_eq!(cf.to_toolset().to_string(), "tiny@3.1.0");
+    }
+
+    #[test]
+    fn test_legacy_files_cache_key() {
+        let settings = Settings::get();
+        let tools = legacy_tools(&settings);
+        // dummy has no legacy filenames so it doesn't need to be asked for them
+        assert!(tools.iter().any(|t| t.id() == "tiny"));
+        assert!(!tools.iter().any(|t| t.id() == "dummy"));
+        let key = legacy_files_cache_key(&settings, &tools);
+        assert_eq!(key, legacy_files_cache_key(&settings, &tools));
+        let disabled = Settings {
+            legacy_version_file_disable_tools: BTreeSet::from(["tiny".into()]),
+            ..(*settings).clone()
+        };
+        let disabled_tools = legacy_tools(&disabled);
+        assert_ne!(key, legacy_files_cache_key(&disabled, &disabled_tools));
+
+        let legacy_files = load_legacy_files(&settings);
+        assert_eq!(legacy_files[".tiny-version"], vec!["tiny"]);
+        let cache_file = dirs::CACHE
+            .join("legacy_filenames")
+            .join(format!("{key}.msgpack.z"));
+        assert!(cache_file.exists());
+        assert_eq!(load_legacy_files(&settings), legacy_files);
+    }
+
+    #[test]
+    fn test_resolve_alias() {
+        let fa: ForgeArg = "tiny".parse().unwrap();
+        let tiny = forge::get(&fa);
+        let aliases = |pairs: &[(&str, &str)]| {
+            let pairs = pairs.iter().map(|(k, v)| (k.to_string(), v.to_string()));
+            AliasMap::from([(fa.clone(), pairs.collect())])
+        };
+        let config = Config {
+            aliases: aliases(&[("lts", "stable"), ("stable", "3.1.0"), ("self", "self")]),
+            ..Default::default()
+        };
+        assert_eq!(config.resolve_alias(tiny.as_ref(), "lts").unwrap(), "3.1.0");
+        assert_eq!(config.resolve_alias(tiny.as_ref(), "self").unwrap(), "self");
+        assert_eq!(config.resolve_alias(tiny.as_ref(), "2.0").unwrap(), "2.0");
+        // the forge's aliases are used only if no config file alias matched
+        assert_eq!(
+            config.resolve_alias(tiny.as_ref(), "lts-prev").unwrap(),
+            "2.0.0"
+        );
+        let config = Config {
+            aliases: aliases(&[("prev", "lts-prev")]),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.resolve_alias(tiny.as_ref(), "prev").unwrap(),
+            "lts-prev"
+        );
+
+        let config = Config {
+            aliases: aliases(&[("lts", "20"), ("20", "lts")]),
+            ..Default::default()
+        };
+        let err = config.resolve_alias(tiny.as_ref(), "lts").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "circular alias detected for tiny: lts -> 20 -> lts"
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fmt::{Debug, Display, Formatter};
 use std::iter::once;
 use std::path::PathBuf;
@@ -12,6 +12,8 @@ use once_cell::sync::Lazy;
 use serde::ser::Error;
 use serde_derive::{Deserialize, Serialize};
 
+use crate::config::config_file::mise_toml::deserialize_arr;
+use crate::config::merge::MergeStrategy;
 use crate::config::{system_config_files, DEFAULT_CONFIG_FILENAMES};
 use crate::{config, dirs, env, file};
 
@@ -50,10 +52,27 @@ pub struct Settings {
     pub legacy_version_file: bool,
     #[config(env = "MISE_LEGACY_VERSION_FILE_DISABLE_TOOLS", default = [], parse_env = list_by_comma)]
     pub legacy_version_file_disable_tools: BTreeSet<String>,
+    /// additional legacy version files to read, mapping each filename to a tool
+    /// e.g.: `{ ".myrc" = "mytool" }` or MISE_LEGACY_VERSION_FILENAMES=.myrc=mytool
+    #[config(env = "MISE_LEGACY_VERSION_FILENAMES", default = {}, parse_env = map_by_comma)]
+    pub legacy_version_filenames: BTreeMap<String, String>,
+    /// also read versions from other package managers' manifests like the `engines.node`
+    /// field of package.json, open-ended ranges like `>=18` are ignored
+    #[config(env = "MISE_LEGACY_VERSION_MANIFESTS", default = true)]
+    pub legacy_version_manifests: bool,
+    /// how env paths and alias tables from different config files are combined
+    /// "append" (default) or "replace", env paths can be overridden per file with `_.merge`
+    #[config(env = "MISE_MERGE_STRATEGY", default = "append")]
+    pub merge_strategy: MergeStrategy,
     #[config(env = "MISE_NODE_COMPILE", default = false)]
     pub node_compile: bool,
     #[config(env = "MISE_NOT_FOUND_AUTO_INSTALL", default = true)]
     pub not_found_auto_install: bool,
+    /// machine-specific config file that is loaded with the highest precedence from
+    /// anywhere above the current directory, it is meant to be ignored by version control.
+    /// Project config files are found with this name so it can't be set in them.
+    #[config(env = "MISE_OVERRIDE_CONFIG_FILENAME", default = ".mise.override.toml")]
+    pub override_config_filename: String,
     #[config(env = "MISE_PARANOID", default = false)]
     pub paranoid: bool,
     #[config(env = "MISE_PLUGIN_AUTOUPDATE_LAST_CHECK_DURATION", default = "7d")]
@@ -81,9 +100,19 @@ pub struct Settings {
     pub raw: bool,
     #[config(env = "MISE_SHORTHANDS_FILE")]
     pub shorthands_file: Option<PathBuf>,
+    /// skip config files that fail to parse instead of failing to load any config, the file set
+    /// with MISE_CONFIG_FILE must always parse
+    #[config(env = "MISE_SKIP_INVALID_CONFIG_FILES", default = false)]
+    pub skip_invalid_config_files: bool,
     /// what level of status messages to display when entering directories
     #[config(nested)]
     pub status: SettingsStatus,
+    /// fail on unknown keys in config files instead of warning and ignoring them
+    #[config(env = "MISE_STRICT_CONFIG", default = false)]
+    pub strict_config: bool,
+    /// fail if any env entry can't be resolved instead of warning and skipping it
+    #[config(env = "MISE_STRICT_ENV", default = false)]
+    pub strict_env: bool,
     #[config(env = "MISE_TASK_OUTPUT")]
     pub task_output: Option<String>,
     #[config(env = "MISE_TRUSTED_CONFIG_PATHS", default = [], parse_env = list_by_colon)]
@@ -102,8 +131,8 @@ pub struct Settings {
     pub cd: Option<String>,
     #[config(env = "MISE_DEBUG", default = false)]
     pub debug: bool,
-    #[config(env = "MISE_ENV_FILE")]
-    pub env_file: Option<PathBuf>,
+    #[config(env = "MISE_ENV_FILE", default = [], parse_env = list_by_colon, deserialize_with = deserialize_arr)]
+    pub env_file: Vec<PathBuf>,
     #[config(env = "MISE_TRACE", default = false)]
     pub trace: bool,
     #[config(env = "MISE_LOG_LEVEL", default = "info")]
@@ -262,6 +291,22 @@ impl Settings {
         Self::parse_settings_file(global_config)
     }
 
+    /// project config files are found with the override filename so it can only come from
+    /// the cli, env or global and system config files
+    fn override_config_filename() -> String {
+        let cli = CLI_SETTINGS.lock().unwrap().clone().unwrap_or_default();
+        cli.override_config_filename
+            .or_else(|| env::var("MISE_OVERRIDE_CONFIG_FILENAME").ok())
+            .or_else(|| Self::config_settings().ok()?.override_config_filename)
+            .or_else(|| {
+                system_config_files()
+                    .iter()
+                    .find_map(|p| Self::parse_settings_file(p).ok()?.override_config_filename)
+            })
+            .or_else(|| SettingsPartial::default_values().override_config_filename)
+            .unwrap_or_default()
+    }
+
     fn deprecated_settings_file() -> Result<SettingsPartial> {
         // TODO: show warning and merge with config file in a few weeks
         let settings_file = &*env::MISE_SETTINGS_FILE;
@@ -278,7 +323,7 @@ impl Settings {
     }
 
     fn all_settings_files() -> Vec<SettingsPartial> {
-        config::load_config_paths(&DEFAULT_CONFIG_FILENAMES)
+        config::load_config_paths(&DEFAULT_CONFIG_FILENAMES, &Self::override_config_filename())
             .iter()
             .filter(|p| {
                 let filename = p.file_name().unwrap_or_default().to_string_lossy();
@@ -311,6 +356,12 @@ impl Settings {
         HIDDEN_CONFIGS.clone()
     }
 
+    /// re-reads the settings from the environment and config files on next access, keeping
+    /// the settings that were set by cli flags
+    pub fn reload() {
+        *SETTINGS.write().unwrap() = None;
+    }
+
     pub fn reset(cli_settings: Option<SettingsPartial>) {
         *CLI_SETTINGS.lock().unwrap() = cli_settings;
         *SETTINGS.write().unwrap() = None;
@@ -340,6 +391,21 @@ impl Settings {
     }
 }
 
+/// parses "key1=value1,key2=value2"
+fn map_by_comma(input: &str) -> std::io::Result<BTreeMap<String, String>> {
+    input
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .map(|s| match s.split_once('=') {
+            Some((k, v)) => Ok((k.trim().to_string(), v.trim().to_string())),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("expected key=value: {s}"),
+            )),
+        })
+        .collect()
+}
+
 impl Display for Settings {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match serde_json::to_string_pretty(self) {
@@ -54,8 +54,13 @@ pub static MISE_DEFAULT_TOOL_VERSIONS_FILENAME: Lazy<String> = Lazy::new(|| {
 });
 pub static MISE_DEFAULT_CONFIG_FILENAME: Lazy<String> =
     Lazy::new(|| var("MISE_DEFAULT_CONFIG_FILENAME").unwrap_or_else(|_| ".mise.toml".into()));
-pub static MISE_ENV: Lazy<Option<String>> =
-    Lazy::new(|| var("MISE_ENV").or_else(|_| var("MISE_ENVIRONMENT")).ok());
+/// config profiles to load, e.g.: "ci,docker" loads ".mise.ci.toml" then ".mise.docker.toml"
+pub static MISE_ENV: Lazy<Vec<String>> = Lazy::new(|| {
+    var("MISE_ENV")
+        .or_else(|_| var("MISE_ENVIRONMENT"))
+        .map(|s| split_mise_env(&s))
+        .unwrap_or_default()
+});
 pub static MISE_SETTINGS_FILE: Lazy<PathBuf> = Lazy::new(|| {
     var_path("MISE_SETTINGS_FILE").unwrap_or_else(|| MISE_CONFIG_DIR.join("settings.toml"))
 });
@@ -65,6 +70,8 @@ pub static MISE_GLOBAL_CONFIG_FILE: Lazy<PathBuf> = Lazy::new(|| {
         .unwrap_or_else(|| MISE_CONFIG_DIR.join("config.toml"))
 });
 pub static MISE_USE_TOML: Lazy<bool> = Lazy::new(|| var_is_true("MISE_USE_TOML"));
+/// skip the on-disk cache of resolved `[env]` values and always resolve them again
+pub static MISE_NO_ENV_CACHE: Lazy<bool> = Lazy::new(|| var_is_true("MISE_NO_ENV_CACHE"));
 pub static ARGV0: Lazy<String> = Lazy::new(|| ARGS.read().unwrap()[0].to_string());
 pub static MISE_BIN_NAME: Lazy<&str> = Lazy::new(|| filename(&ARGV0));
 pub static MISE_LOG_FILE: Lazy<Option<PathBuf>> = Lazy::new(|| var_path("MISE_LOG_FILE"));
@@ -245,6 +252,14 @@ fn get_env_diff() -> EnvDiff {
     }
 }
 
+fn split_mise_env(s: &str) -> Vec<String> {
+    s.split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
 fn var_is_true(key: &str) -> bool {
     match var(key) {
         Ok(v) => {
@@ -409,4 +424,12 @@ mod tests {
         );
         remove_var("MISE_TEST_PATH");
     }
+
+    #[test]
+    fn test_split_mise_env() {
+        assert_eq!(split_mise_env("ci"), vec!["ci"]);
+        assert_eq!(split_mise_env("ci,docker"), vec!["ci", "docker"]);
+        assert_eq!(split_mise_env(" ci, ,docker,"), vec!["ci", "docker"]);
+        assert!(split_mise_env("").is_empty());
+    }
 }
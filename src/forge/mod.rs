@@ -13,6 +13,7 @@ use regex::Regex;
 use versions::Versioning;
 
 use crate::cli::args::ForgeArg;
+use crate::config::config_file::legacy_version::LegacyManifest;
 use crate::config::{Config, Settings};
 use crate::file::{display_path, remove_all, remove_all_with_warning};
 use crate::forge::cargo::CargoForge;
@@ -221,9 +222,19 @@ pub trait Forge: Debug + Send + Sync {
     fn get_aliases(&self) -> eyre::Result<BTreeMap<String, String>> {
         Ok(BTreeMap::new())
     }
+    /// false if the tool has no legacy version files so `legacy_filenames`, which may run a
+    /// plugin script, doesn't need to be called
+    fn has_legacy_filenames(&self) -> bool {
+        false
+    }
     fn legacy_filenames(&self) -> eyre::Result<Vec<String>> {
         Ok(vec![])
     }
+    /// package manager manifests that can pin a version of this tool, these are read along
+    /// with `legacy_filenames`
+    fn legacy_manifests(&self) -> Vec<LegacyManifest> {
+        vec![]
+    }
     fn parse_legacy_file(&self, path: &Path) -> eyre::Result<String> {
         let contents = file::read_to_string(path)?;
         Ok(contents.trim().to_string())
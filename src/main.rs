@@ -67,6 +67,7 @@ mod runtime_symlinks;
 mod shell;
 mod shims;
 mod shorthands;
+mod sops;
 mod task;
 pub mod tera;
 pub mod timeout;
@@ -107,6 +107,10 @@ impl Forge for BunPlugin {
             .cloned()
     }
 
+    fn has_legacy_filenames(&self) -> bool {
+        true
+    }
+
     fn legacy_filenames(&self) -> Result<Vec<String>> {
         Ok(vec![".bun-version".into()])
     }
@@ -106,6 +106,10 @@ impl Forge for DenoPlugin {
             .cloned()
     }
 
+    fn has_legacy_filenames(&self) -> bool {
+        true
+    }
+
     fn legacy_filenames(&self) -> Result<Vec<String>> {
         Ok(vec![".deno-version".into()])
     }
@@ -145,6 +145,10 @@ impl Forge for GoPlugin {
             .get_or_try_init(|| self.fetch_remote_versions())
             .cloned()
     }
+    fn has_legacy_filenames(&self) -> bool {
+        true
+    }
+
     fn legacy_filenames(&self) -> Result<Vec<String>> {
         Ok(vec![".go-version".into()])
     }
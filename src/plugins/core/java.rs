@@ -339,6 +339,10 @@ impl Forge for JavaPlugin {
         Ok(map)
     }
 
+    fn has_legacy_filenames(&self) -> bool {
+        true
+    }
+
     fn legacy_filenames(&self) -> Result<Vec<String>> {
         Ok(vec![".java-version".into(), ".sdkmanrc".into()])
     }
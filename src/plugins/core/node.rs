@@ -9,6 +9,7 @@ use url::Url;
 use crate::build_time::built_info;
 use crate::cli::args::ForgeArg;
 use crate::cmd::CmdLineRunner;
+use crate::config::config_file::legacy_version::LegacyManifest;
 use crate::config::{Config, Settings};
 use crate::env::MISE_NODE_MIRROR_URL;
 use crate::forge::Forge;
@@ -274,10 +275,18 @@ impl Forge for NodePlugin {
         Ok(aliases)
     }
 
+    fn has_legacy_filenames(&self) -> bool {
+        true
+    }
+
     fn legacy_filenames(&self) -> Result<Vec<String>> {
         Ok(vec![".node-version".into(), ".nvmrc".into()])
     }
 
+    fn legacy_manifests(&self) -> Vec<LegacyManifest> {
+        vec![LegacyManifest::PackageJsonEngine("node")]
+    }
+
     fn parse_legacy_file(&self, path: &Path) -> Result<String> {
         let body = file::read_to_string(path)?;
         // trim "v" prefix
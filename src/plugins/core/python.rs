@@ -8,6 +8,7 @@ use crate::build_time::built_info;
 use crate::cache::CacheManager;
 use crate::cli::args::ForgeArg;
 use crate::cmd::CmdLineRunner;
+use crate::config::config_file::legacy_version::LegacyManifest;
 use crate::config::{Config, Settings};
 use crate::file::display_path;
 use crate::forge::Forge;
@@ -302,10 +303,18 @@ impl Forge for PythonPlugin {
             .cloned()
     }
 
+    fn has_legacy_filenames(&self) -> bool {
+        true
+    }
+
     fn legacy_filenames(&self) -> Result<Vec<String>> {
         Ok(vec![".python-version".to_string()])
     }
 
+    fn legacy_manifests(&self) -> Vec<LegacyManifest> {
+        vec![LegacyManifest::PyprojectRequiresPython]
+    }
+
     fn install_version_impl(&self, ctx: &InstallContext) -> Result<()> {
         let config = Config::get();
         let settings = Settings::try_get()?;
@@ -600,6 +600,10 @@ impl Forge for ExternalPlugin {
         Ok(aliases)
     }
 
+    fn has_legacy_filenames(&self) -> bool {
+        self.toml.list_legacy_filenames.data.is_some() || self.has_list_legacy_filenames_script()
+    }
+
     fn legacy_filenames(&self) -> Result<Vec<String>> {
         if let Some(data) = &self.toml.list_legacy_filenames.data {
             return Ok(self.parse_legacy_filenames(data));
@@ -0,0 +1,62 @@
+use std::path::Path;
+
+use eyre::{Context, Result};
+
+use crate::file;
+
+/// true if an env file is encrypted with sops, either by its name (e.g.: `.env.sops.json`,
+/// `.enc.env`) or because it contains sops metadata
+pub fn is_encrypted(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    if name.contains(".sops.") || name.ends_with(".enc.env") {
+        return true;
+    }
+    let Ok(body) = file::read_to_string(path) else {
+        return false;
+    };
+    body.lines().any(|l| {
+        l.starts_with("sops:") || l.starts_with("sops_version=") || l.trim() == r#""sops": {"#
+    })
+}
+
+/// decrypts an env file with `sops --decrypt`. The output is only kept in memory and never
+/// written to disk.
+pub fn decrypt_env(path: &Path) -> Result<Vec<(String, String)>> {
+    let output = cmd!("sops", "--decrypt", "--output-type", "dotenv", path)
+        .stdout_capture()
+        .stderr_capture()
+        .unchecked()
+        .run()
+        .wrap_err("failed to run sops, is it installed?")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("sops failed: {}", stderr.trim());
+    }
+    dotenvy::from_read_iter(&output.stdout[..])
+        .collect::<Result<Vec<_>, _>>()
+        .wrap_err("failed to parse sops output")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let check = |name: &str, body: &str| {
+            let path = dir.path().join(name);
+            file::write(&path, body).unwrap();
+            is_encrypted(&path)
+        };
+        assert!(check(".env.sops.json", "{}"));
+        assert!(check("secrets.enc.env", "FOO=bar"));
+        assert!(check(
+            ".env.json",
+            "{\n  \"FOO\": \"ENC[x]\",\n  \"sops\": {\n  }\n}"
+        ));
+        assert!(check(".env", "FOO=ENC[x]\nsops_version=3.8.1\n"));
+        assert!(!check(".env", "FOO=bar\n"));
+        assert!(!check(".env.json", "{\"FOO\": \"bar\"}"));
+    }
+}
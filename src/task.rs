@@ -9,6 +9,7 @@ use std::ffi;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
+use std::iter::once;
 use std::path;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc;
@@ -19,13 +20,19 @@ use itertools::Itertools;
 
 use crate::config::config_file::toml::TomlParser;
 use crate::config::Config;
-use crate::file;
+use crate::duration::DAILY;
+use crate::git::Git;
+use crate::hash::{ensure_checksum_sha256, hash_to_str};
+use crate::http::HTTP_FETCH;
 use crate::tera::{get_tera, BASE_CONTEXT};
 use crate::ui::tree::TreeItem;
+use crate::{dirs, env, file};
 
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
 pub struct Task {
     pub name: String,
+    /// true if `name` comes from the file's path in a tasks dir, e.g.: `ci/build` -> `ci:build`
+    pub derived_name: bool,
     pub description: String,
     pub aliases: Vec<String>,
     pub config_source: PathBuf,
@@ -49,6 +56,10 @@ pub struct Task {
 
     // file type
     pub file: Option<PathBuf>,
+    /// `https://` or `git::` url the file was fetched from
+    pub remote: Option<String>,
+    /// sha256 the file must match before it is executed
+    pub checksum: Option<String>,
 }
 
 impl Task {
@@ -60,6 +71,13 @@ impl Task {
         }
     }
     pub fn from_path(path: &Path) -> Result<Task> {
+        let config_root =
+            config_root(&path).ok_or_else(|| eyre!("config root not found: {}", path.display()))?;
+        Task::from_file(path, name_from_path(config_root, path)?, config_root)
+    }
+
+    /// `name` is used unless the file sets one with `# mise name=...`
+    fn from_file(path: &Path, name: String, config_root: &Path) -> Result<Task> {
         let info = file::read_to_string(path)?
             .lines()
             .filter_map(|line| regex!(r"^# mise ([a-z]+=.+)$").captures(line))
@@ -75,14 +93,14 @@ impl Task {
                 map
             });
         let info = toml::Value::Table(info);
-        let config_root =
-            config_root(&path).ok_or_else(|| eyre!("config root not found: {}", path.display()))?;
         let mut tera_ctx = BASE_CONTEXT.clone();
         tera_ctx.insert("config_root", &config_root);
         let p = TomlParser::new(&info, get_tera(Some(config_root)), tera_ctx);
         // trace!("task info: {:#?}", info);
 
+        let explicit_name = p.parse_str("name")?;
         let task = Task {
+            derived_name: explicit_name.is_none(),
             hide: !file::is_executable(path) || p.parse_bool("hide").unwrap_or_default(),
             description: p.parse_str("description")?.unwrap_or_default(),
             sources: p.parse_array("sources")?.unwrap_or_default(),
@@ -91,11 +109,65 @@ impl Task {
             dir: p.parse_str("dir")?,
             env: p.parse_hashmap("env")?.unwrap_or_default(),
             file: Some(path.to_path_buf()),
-            ..Task::new(name_from_path(config_root, path)?, path.to_path_buf())
+            ..Task::new(explicit_name.unwrap_or(name), path.to_path_buf())
         };
         Ok(task)
     }
 
+    /// downloads the file for a task defined with a `remote` url into the cache dir and parses
+    /// it like a local task file. Properties set in the config file take precedence over the
+    /// ones in the file's `# mise` comments.
+    pub fn fetch_remote(self) -> Result<Task> {
+        let Some(url) = &self.remote else {
+            return Ok(self);
+        };
+        let path = fetch_remote_file(url, self.checksum.as_deref())?;
+        let config_root = self.config_source.parent().unwrap_or(Path::new("/"));
+        let from_file = Task::from_file(&path, self.name.clone(), config_root)?;
+        let mut env = from_file.env;
+        env.extend(self.env);
+        Ok(Task {
+            description: match self.description.is_empty() {
+                true => from_file.description,
+                false => self.description,
+            },
+            depends: match self.depends.is_empty() {
+                true => from_file.depends,
+                false => self.depends,
+            },
+            sources: match self.sources.is_empty() {
+                true => from_file.sources,
+                false => self.sources,
+            },
+            outputs: match self.outputs.is_empty() {
+                true => from_file.outputs,
+                false => self.outputs,
+            },
+            env,
+            dir: self.dir.or(from_file.dir),
+            hide: self.hide || from_file.hide,
+            file: Some(path),
+            ..self
+        })
+    }
+
+    /// where the file of a task with a `remote` url is cached, it may not have been fetched yet
+    pub fn remote_cache_path(&self) -> Option<PathBuf> {
+        let url = self.remote.as_deref()?;
+        match url.strip_prefix("git::") {
+            Some(url) => git_cache(url).ok().map(|(dir, _, _, path)| dir.join(path)),
+            None => Some(http_cache_path(url)),
+        }
+    }
+
+    /// errors if the task file doesn't match the `checksum` set for the task
+    pub fn verify_checksum(&self) -> Result<()> {
+        match (&self.checksum, &self.file) {
+            (Some(checksum), Some(file)) => verify_checksum(file, checksum),
+            _ => Ok(()),
+        }
+    }
+
     pub fn command_string(&self) -> Option<String> {
         if let Some(command) = self.run.first() {
             Some(command.to_string())
@@ -160,6 +232,46 @@ fn name_from_path(root: impl AsRef<Path>, path: impl AsRef<Path>) -> Result<Stri
         .join(":"))
 }
 
+/// errors if any tasks depend on each other in a cycle, e.g.: "build -> test -> build"
+/// `tasks` is keyed by name and alias so dependencies on aliases resolve to the same task
+pub fn check_cycles(tasks: &HashMap<String, Task>) -> Result<()> {
+    let mut graph: DiGraph<&str, ()> = DiGraph::new();
+    let mut indexes = HashMap::new();
+    let sorted = tasks.values().unique().sorted().collect_vec();
+    for t in &sorted {
+        indexes
+            .entry(t.name.as_str())
+            .or_insert_with(|| graph.add_node(t.name.as_str()));
+    }
+    for a in &sorted {
+        let a_idx = indexes[a.name.as_str()];
+        for pat in &a.depends {
+            // missing dependencies are reported when the task is run
+            for b in tasks.get_matching(pat)? {
+                graph.update_edge(a_idx, indexes[b.name.as_str()], ());
+            }
+        }
+    }
+    if let Err(cycle) = petgraph::algo::toposort(&graph, None) {
+        let start = cycle.node_id();
+        let mut path = graph
+            .neighbors(start)
+            .find_map(|n| petgraph::algo::astar(&graph, n, |n| n == start, |_| 1, |_| 0))
+            .map(|(_, path)| path)
+            .unwrap_or_else(|| vec![start]);
+        // start the cycle from the first task alphabetically so the message is stable
+        let first = path.iter().position_min().unwrap_or_default();
+        path.rotate_left(first);
+        let path = path
+            .iter()
+            .chain(once(&path[0]))
+            .map(|&idx| graph[idx])
+            .join(" -> ");
+        bail!("circular dependency detected in tasks: {path}");
+    }
+    Ok(())
+}
+
 fn match_tasks<'a>(tasks: &'a HashMap<String, Task>, pat: &str) -> Result<Vec<&'a Task>> {
     let matches = tasks.get_matching(pat)?;
     if matches.is_empty() {
@@ -209,26 +321,47 @@ impl Deps {
         let mut graph = DiGraph::new();
         let mut indexes = HashMap::new();
         let mut stack = vec![];
+        // remote task files are only fetched once they are needed, they may add dependencies
         for t in tasks {
-            stack.push(t.clone());
-            indexes
-                .entry(t.name.clone())
-                .or_insert_with(|| graph.add_node(t));
+            let t = t.fetch_remote()?;
+            if !indexes.contains_key(&t.name) {
+                indexes.insert(t.name.clone(), graph.add_node(t.clone()));
+                stack.push(t);
+            }
         }
         while let Some(a) = stack.pop() {
-            let a_idx = *indexes
-                .entry(a.name.clone())
-                .or_insert_with(|| graph.add_node(a.clone()));
+            let a_idx = indexes[&a.name];
             for b in a.resolve_depends(config)? {
-                let b_idx = *indexes
-                    .entry(b.name.clone())
-                    .or_insert_with(|| graph.add_node(b.clone()));
+                let b_idx = match indexes.get(&b.name) {
+                    Some(idx) => *idx,
+                    None => {
+                        let b = b.clone().fetch_remote()?;
+                        let idx = graph.add_node(b.clone());
+                        indexes.insert(b.name.clone(), idx);
+                        stack.push(b);
+                        idx
+                    }
+                };
                 if !graph.contains_edge(a_idx, b_idx) {
                     graph.add_edge(a_idx, b_idx, ());
                 }
-                stack.push(b.clone());
             }
         }
+        // remote task files may add dependencies so the fetched tasks replace the local ones
+        let fetched = graph
+            .node_weights()
+            .map(|t| (t.name.as_str(), t))
+            .collect::<HashMap<_, _>>();
+        let mut tasks = config.tasks_with_aliases().clone();
+        for t in tasks.values_mut() {
+            if let Some(f) = fetched.get(t.name.as_str()) {
+                *t = (*f).clone();
+            }
+        }
+        for t in graph.node_weights() {
+            tasks.entry(t.name.clone()).or_insert_with(|| t.clone());
+        }
+        check_cycles(&tasks)?;
         let (tx, _) = mpsc::channel();
         let sent = HashSet::new();
         Ok(Self { graph, tx, sent })
@@ -326,6 +459,135 @@ fn config_root(config_source: &impl AsRef<Path>) -> Option<&Path> {
     Some(config_source.as_ref())
 }
 
+/// true if a task `file` should be fetched rather than read from disk
+pub fn is_remote_url(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("git::")
+}
+
+fn verify_checksum(path: &Path, checksum: &str) -> Result<()> {
+    match checksum.split_once(':') {
+        Some(("sha256", hash)) => ensure_checksum_sha256(path, hash),
+        Some((algo, _)) => bail!("unsupported checksum algorithm: {algo}"),
+        None => ensure_checksum_sha256(path, checksum),
+    }
+}
+
+/// returns the path to the cached copy of a remote task file, refreshing it daily
+/// if it can't be refreshed (e.g.: offline) the previously cached copy is used
+fn fetch_remote_file(url: &str, checksum: Option<&str>) -> Result<PathBuf> {
+    match url.strip_prefix("git::") {
+        Some(url) => fetch_remote_git(url),
+        None => fetch_remote_http(url, checksum),
+    }
+}
+
+fn http_cache_path(url: &str) -> PathBuf {
+    let filename = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|u| u.rsplit('/').next())
+        .filter(|f| !f.is_empty())
+        .unwrap_or("task");
+    dirs::CACHE
+        .join("remote-tasks")
+        .join(hash_to_str(&url))
+        .join(filename)
+}
+
+fn fetch_remote_http(url: &str, checksum: Option<&str>) -> Result<PathBuf> {
+    let path = http_cache_path(url);
+    let dir = path.parent().unwrap();
+    let filename = path.file_name().unwrap().to_string_lossy();
+    if path.exists() {
+        let pinned = checksum.is_some_and(|c| verify_checksum(&path, c).is_ok());
+        if pinned || *env::PREFER_STALE || file::modified_duration(&path)? < DAILY {
+            return Ok(path);
+        }
+    }
+    let tmp = dir.join(format!("{filename}.part"));
+    let fetched = HTTP_FETCH
+        .download_file(url, &tmp)
+        .and_then(|_| match checksum {
+            Some(checksum) => verify_checksum(&tmp, checksum),
+            None => Ok(()),
+        });
+    match fetched {
+        Ok(()) => {
+            file::rename(&tmp, &path)?;
+            file::make_executable(&path)?;
+        }
+        Err(err) => {
+            if tmp.exists() {
+                file::remove_file(&tmp)?;
+            }
+            if !path.exists() {
+                return Err(err.wrap_err(format!("failed to fetch task {url}")));
+            }
+            warn!("failed to fetch task {url}, using cached copy: {err:#}");
+        }
+    }
+    Ok(path)
+}
+
+/// the clone dir, repo url, ref and path in the repo of a git task url
+fn git_cache(url: &str) -> Result<(PathBuf, &str, Option<String>, &str)> {
+    let (url, gitref) = match url.split_once("?ref=") {
+        Some((url, gitref)) => (url, Some(gitref.to_string())),
+        None => (url, None),
+    };
+    let (repo, path) = split_git_path(url).ok_or_else(|| {
+        eyre!("git task url must include the path to the file after `//`: git::{url}")
+    })?;
+    // the path is joined onto the clone dir so it must not be able to point outside of it
+    ensure!(
+        Path::new(path)
+            .components()
+            .all(|c| matches!(c, path::Component::Normal(_) | path::Component::CurDir)),
+        "git task path must be relative to the repo and not contain `..`: git::{url}"
+    );
+    let dir = dirs::CACHE
+        .join("remote-tasks")
+        .join(hash_to_str(&(repo, &gitref)));
+    Ok((dir, repo, gitref, path))
+}
+
+/// fetches a file from a git repo, e.g.: `git::https://github.com/org/repo.git//tasks/lint?ref=v1`
+fn fetch_remote_git(url: &str) -> Result<PathBuf> {
+    let (dir, repo, gitref, path) = git_cache(url)?;
+    // the clone dir's mtime doesn't reliably change on update so a marker file next to it
+    // records when it was last fetched
+    let fetched = dir.with_extension("fetched");
+    let mark_fetched = || file::write(&fetched, "").and_then(|_| file::touch_dir(&fetched));
+    let git = Git::new(dir.clone());
+    if !git.is_repo() {
+        git.clone(repo)?;
+        if gitref.is_some() {
+            git.update(gitref)?;
+        }
+        mark_fetched()?;
+    } else if !*env::PREFER_STALE && file::modified_duration(&fetched).map_or(true, |d| d > DAILY) {
+        match git.update(gitref) {
+            Ok(_) => mark_fetched()?,
+            Err(err) => warn!("failed to update task repo {repo}, using cached copy: {err:#}"),
+        }
+    }
+    let file = dir.join(path);
+    ensure!(file.exists(), "task file {path} not found in {repo}");
+    // symlinks in the repo could still lead outside of it
+    ensure!(
+        file.canonicalize()?.starts_with(dir.canonicalize()?),
+        "task file {path} is outside of {repo}"
+    );
+    Ok(file)
+}
+
+/// splits "https://host/repo.git//path/to/file" into the repo url and the path in the repo
+fn split_git_path(url: &str) -> Option<(&str, &str)> {
+    let start = url.find("://").map(|i| i + 3).unwrap_or_default();
+    let (repo, path) = url[start..].split_once("//")?;
+    Some((&url[..start + repo.len()], path))
+}
+
 pub trait GetMatchingExt<T> {
     fn get_matching(&self, pat: &str) -> Result<Vec<&T>>;
 }
@@ -353,9 +615,16 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::{config_root, name_from_path};
+    use super::{
+        check_cycles, config_root, git_cache, http_cache_path, name_from_path, split_git_path,
+        verify_checksum, Deps, Task,
+    };
+    use std::collections::HashMap;
     use std::path::Path;
 
+    use crate::config::Config;
+    use crate::file;
+
     #[test]
     fn test_name_from_path() {
         let test_cases = [
@@ -392,4 +661,177 @@ mod tests {
             assert_eq!(config_root(&src), expected)
         }
     }
+
+    #[test]
+    fn test_from_path_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let tasks_dir = dir.path().join(".mise/tasks/ci");
+        file::create_dir_all(&tasks_dir).unwrap();
+        file::write(tasks_dir.join("build"), "#!/usr/bin/env bash\n").unwrap();
+        file::write(
+            tasks_dir.join("test"),
+            "#!/usr/bin/env bash\n# mise name=\"test\"\n",
+        )
+        .unwrap();
+
+        let build = Task::from_path(&tasks_dir.join("build")).unwrap();
+        assert_eq!(build.name, "ci:build");
+        assert!(build.derived_name);
+        let test = Task::from_path(&tasks_dir.join("test")).unwrap();
+        assert_eq!(test.name, "test");
+        assert!(!test.derived_name);
+    }
+
+    fn tasks(defs: &[(&str, &[&str], &[&str])]) -> HashMap<String, Task> {
+        let mut tasks = HashMap::new();
+        for (name, aliases, depends) in defs {
+            let task = Task {
+                aliases: aliases.iter().map(|s| s.to_string()).collect(),
+                depends: depends.iter().map(|s| s.to_string()).collect(),
+                ..Task::new(name.to_string(), "/.mise.toml".into())
+            };
+            for alias in &task.aliases {
+                tasks.insert(alias.clone(), task.clone());
+            }
+            tasks.insert(task.name.clone(), task);
+        }
+        tasks
+    }
+
+    #[test]
+    fn test_check_cycles() {
+        let diamond = tasks(&[
+            ("a", &[], &["b", "c"]),
+            ("b", &[], &["d"]),
+            ("c", &[], &["d"]),
+            ("d", &[], &[]),
+        ]);
+        assert!(check_cycles(&diamond).is_ok());
+
+        let cycle = tasks(&[("build", &[], &["test"]), ("test", &[], &["build"])]);
+        let err = check_cycles(&cycle).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "circular dependency detected in tasks: build -> test -> build"
+        );
+
+        let aliased = tasks(&[("build", &["b"], &["t"]), ("test", &["t"], &["b"])]);
+        let err = check_cycles(&aliased).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "circular dependency detected in tasks: build -> test -> build"
+        );
+
+        let self_dep = tasks(&[("lint", &[], &["lint"])]);
+        let err = check_cycles(&self_dep).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "circular dependency detected in tasks: lint -> lint"
+        );
+    }
+
+    #[test]
+    fn test_split_git_path() {
+        assert_eq!(
+            split_git_path("https://github.com/org/tasks.git//ci/lint"),
+            Some(("https://github.com/org/tasks.git", "ci/lint"))
+        );
+        assert_eq!(
+            split_git_path("git@github.com:org/tasks.git//lint"),
+            Some(("git@github.com:org/tasks.git", "lint"))
+        );
+        assert_eq!(split_git_path("https://github.com/org/tasks.git"), None);
+    }
+
+    #[test]
+    fn test_git_cache_path_outside_repo() {
+        let (dir, repo, _, path) = git_cache("https://github.com/org/tasks.git//ci/lint").unwrap();
+        assert_eq!(repo, "https://github.com/org/tasks.git");
+        assert!(dir.join(path).starts_with(&dir));
+        for url in [
+            "https://github.com/org/tasks.git//../../../etc/passwd",
+            "https://github.com/org/tasks.git//ci/../../lint",
+            "https://github.com/org/tasks.git///etc/passwd",
+        ] {
+            let err = git_cache(url).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "git task path must be relative to the repo and not contain `..`: git::{url}"
+                )
+            );
+        }
+        let task = Task {
+            remote: Some("git::https://github.com/org/tasks.git//../lint".into()),
+            ..Task::new("lint".into(), "/tmp/.mise.toml".into())
+        };
+        assert_eq!(task.remote_cache_path(), None);
+    }
+
+    #[test]
+    fn test_fetch_remote_cached() {
+        let url = "https://example.com/tasks/test-fetch-remote-cached";
+        let path = http_cache_path(url);
+        file::create_dir_all(path.parent().unwrap()).unwrap();
+        file::write(
+            &path,
+            "#!/bin/sh\n# mise description=\"lint it\"\necho hi\n",
+        )
+        .unwrap();
+        let task = Task {
+            remote: Some(url.to_string()),
+            checksum: Some(
+                "c4739a7d21b809b7092d3cbe398658c3b1abfa042ae8200981407ff3cb67ced3".to_string(),
+            ),
+            ..Task::new("lint".into(), "/tmp/.mise.toml".into())
+        };
+        assert_eq!(task.remote_cache_path(), Some(path.clone()));
+        // the cached copy matches the checksum so this doesn't hit the network
+        let task = task.fetch_remote().unwrap();
+        assert_eq!(task.file, Some(path.clone()));
+        assert_eq!(task.description, "lint it");
+        file::remove_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_deps_remote_cycle() {
+        let url = "https://example.com/tasks/test-deps-remote-cycle";
+        let path = http_cache_path(url);
+        file::create_dir_all(path.parent().unwrap()).unwrap();
+        file::write(
+            &path,
+            "#!/bin/sh\n# mise depends=[\"remote-cycle-build\"]\necho lint\n",
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        file::write(
+            dir.path().join(".test.mise.toml"),
+            format!(
+                "[tasks.remote-cycle-build]\nrun = \"echo build\"\ndepends = [\"remote-cycle-lint\"]\n\
+                 [tasks.remote-cycle-lint]\nfile = \"{url}\"\nchecksum = \"44c915115136bd28c8c06b70d1c024d232d87bdbd7be194e840d259adf1cc3d8\"\n"
+            ),
+        )
+        .unwrap();
+        let config = Config::load_from_dir(dir.path()).unwrap();
+        let build = config.tasks()["remote-cycle-build"].clone();
+        let err = Deps::new(&config, vec![build]).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "circular dependency detected in tasks: remote-cycle-build -> remote-cycle-lint -> remote-cycle-build"
+        );
+        file::remove_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let path = crate::dirs::CACHE.join("test-verify-checksum");
+        file::write(&path, "echo hi\n").unwrap();
+        let hash = "ab08508fdf5ca4da5c4995987bc41c56c048aaa5eeb046417ae4049b7d40286e";
+        assert!(verify_checksum(&path, hash).is_ok());
+        assert!(verify_checksum(&path, &format!("sha256:{hash}")).is_ok());
+        assert!(verify_checksum(&path, &hash.replace('a', "b")).is_err());
+        let err = verify_checksum(&path, "md5:abc").unwrap_err();
+        assert_eq!(err.to_string(), "unsupported checksum algorithm: md5");
+        file::remove_file(&path).unwrap();
+    }
 }